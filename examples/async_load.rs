@@ -0,0 +1,92 @@
+use pbgui_vpin::vpin_dialog::{
+    CachingProvider, DataProvider, LevelMap, PinProvider, PinSpec, SinkError, VpinDialog,
+    VpinSelection,
+};
+use qt_widgets::{cpp_core::MutPtr, QApplication};
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// A provider which takes its time, as a busy service might
+struct SlowProvider {
+    delay: Duration,
+}
+
+impl DataProvider for SlowProvider {
+    fn roles(&mut self) -> Result<Vec<String>, SinkError> {
+        thread::sleep(self.delay);
+        Ok(vec![
+            "anim".to_string(),
+            "model".to_string(),
+            "fx".to_string(),
+        ])
+    }
+
+    fn levels(&mut self, _show: &str) -> Result<LevelMap, SinkError> {
+        thread::sleep(self.delay);
+        let mut levels = LevelMap::new();
+        levels.insert(
+            "RD".to_string(),
+            vec!["0001".to_string(), "0002".to_string()],
+        );
+        Ok(levels)
+    }
+
+    fn sites(&mut self) -> Result<Vec<String>, SinkError> {
+        thread::sleep(self.delay);
+        Err("the sites service is down".into())
+    }
+
+    fn platforms(&mut self) -> Result<Vec<String>, SinkError> {
+        thread::sleep(self.delay);
+        Ok(vec!["linux-el7".to_string(), "linux-el9".to_string()])
+    }
+
+    fn distributions(&mut self, package: &str) -> Result<Vec<String>, SinkError> {
+        Ok(vec![format!("{}-1.2.0", package)])
+    }
+}
+
+impl PinProvider for SlowProvider {
+    fn existing_pins(&mut self, show: &str) -> Result<Vec<PinSpec>, SinkError> {
+        thread::sleep(self.delay * 3);
+        Ok(vec![PinSpec {
+            distribution: "modelpublish-1.1.0".to_string(),
+            level: show.to_string(),
+            role: None,
+            site: None,
+            platform: None,
+            comment: None,
+            ticket: None,
+            effective_from: None,
+            expires_on: None,
+        }])
+    }
+}
+
+fn main() {
+    QApplication::init(|_app| unsafe {
+        let dialog = VpinDialog::create("DEV01", "modelpublish-1.2.0", MutPtr::null());
+        dialog.set_default_stylesheet();
+        // the dialog opens straight away, and each section fills in as its data arrives
+        let delay = Duration::from_secs(1);
+        // the data is cached on disk, so the dialog fills in at once when the example
+        // is run again. Refresh fetches it anew.
+        let cache = Arc::new(Mutex::new(
+            CachingProvider::new(SlowProvider { delay })
+                .with_cache_file(env::temp_dir().join("pbgui-vpin-cache.json")),
+        ));
+        dialog.load_data_cached(cache.clone());
+        dialog.load_existing_pins_async(SlowProvider { delay });
+        dialog.on_accept(|selection: VpinSelection| {
+            for pin in selection.expand() {
+                println!("{}", pin);
+            }
+            Ok(())
+        });
+        dialog.dialog_mut().exec();
+        println!("{:?}", cache.lock().unwrap().stats());
+        0
+    });
+}
@@ -0,0 +1,44 @@
+use pbgui_vpin::vpin_dialog::{LevelMap, MemorySink, PinSpec, VpinDialog};
+use qt_widgets::{cpp_core::MutPtr, QApplication};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn main() {
+    QApplication::init(|_app| unsafe {
+        let dialog = VpinDialog::create("DEV01", "modelpublish-1.2.0", MutPtr::null());
+        dialog.set_default_stylesheet();
+        dialog.set_roles(vec!["anim", "model", "fx"]);
+        dialog.set_sites(vec!["montreal", "vancouver"]);
+        dialog.set_platforms(vec!["linux-el7", "linux-el9"]);
+        let mut levels = LevelMap::new();
+        levels.insert(
+            "RD".to_string(),
+            vec!["0001".to_string(), "0002".to_string()],
+        );
+        dialog.set_levels(levels);
+        dialog.set_existing_pins(vec![PinSpec {
+            distribution: "modelpublish-1.1.0".to_string(),
+            level: "DEV01".to_string(),
+            role: None,
+            site: None,
+            platform: None,
+            comment: None,
+            ticket: None,
+            effective_from: None,
+            expires_on: None,
+        }]);
+        // each pin records why it was made, and the ticket tracking it
+        dialog.set_comment_required(true);
+        dialog
+            .set_ticket_pattern(Some("[A-Z]+-[0-9]+"))
+            .expect("the ticket pattern is valid");
+        // the pins are written to the sink when the user presses OK
+        let sink = Rc::new(RefCell::new(MemorySink::new()));
+        dialog.commit_to(sink.clone());
+        dialog.dialog_mut().exec();
+        for pin in sink.borrow().pins() {
+            println!("{} ({:?}, {:?})", pin, pin.ticket, pin.comment);
+        }
+        0
+    });
+}
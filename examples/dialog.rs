@@ -1,5 +1,5 @@
 use pbgui_vpin::vpin_dialog;
-use pbgui_vpin::vpin_dialog::LevelMap;
+use pbgui_vpin::vpin_dialog::{LevelMap, VpinSelection};
 use qt_core::{Slot, SlotOfInt};
 use qt_widgets::cpp_core::MutPtr;
 use qt_widgets::QApplication;
@@ -19,36 +19,29 @@ fn main() {
         // we can create and hook up a finished slot. However, the finished slot will be activated
         // whether the user selects Ok or Cancel.
         dialog.set_show_name("DEV02");
-        dialog.set_distribution("modelpublish-1.3.0");
+        dialog.set_distributions(vec!["modelpublish-1.3.0", "modelpublish-plugin-1.3.0"]);
         let finished_slot = SlotOfInt::new(move |result: std::os::raw::c_int| {
             println!("finished_slot -> {}", result);
         });
 
         dialog.finished().connect(&finished_slot);
 
-        // we create a slot that is triggered when OK is pressed to act only in the event
-        // that the user has requested action.
-        let accepted_slot = Slot::new(enclose! { (dialog) move || {
-            if let Some(roles) = dialog.selected_roles() {
+        // we register a callback that is invoked when OK is pressed to act only in the event
+        // that the user has requested action. Returning an Err keeps the dialog open.
+        dialog.on_accept(|selection: VpinSelection| {
+            println!("distributions: {:?}", selection.distributions);
+            if let Some(roles) = selection.roles.as_ref() {
                 println!("roles: {:?}", roles);
             } else {
                 println!("roles: any");
             }
-            if let Some(selected_level) = dialog.selected_level() {
-                println!("level: {:?}", selected_level);
-            } else {
-                println!("level: {}", dialog.show_name());
-            }
-            match dialog.selected_site(){
-                Some(site) => println!(
-                    "site:  {}", site
-                ),
+            println!("level: {}", selection.level_or_show());
+            match selection.site.as_ref() {
+                Some(site) => println!("site:  {}", site),
                 None => println!("site:  Any"),
             }
-            dialog.accept();
-        }});
-        // Connect the accepted signal to the accepted slot
-        dialog.accepted().connect(&accepted_slot);
+            Ok(())
+        });
 
         let exec_dialog_slot = Slot::new(enclose! { (dialog) move || {
             let result = dialog.dialog_mut().exec(); //
@@ -74,7 +67,12 @@ unsafe fn create_dialog<'a, I: Into<String>>(
     let levelmap = initialize_levelmap();
     dialog.set_levels(levelmap);
 
-    dialog.set_sites(vec!["hyderabad", "montreal", "playa", "vancouver"]);
+    dialog.set_site_labels(vec![
+        ("hyderabad", "Hyderabad (HYD)"),
+        ("montreal", "Montreal (MTL)"),
+        ("playa", "Playa Vista (PLV)"),
+        ("vancouver", "Vancouver (VAN)"),
+    ]);
     dialog
 }
 
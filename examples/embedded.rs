@@ -0,0 +1,45 @@
+use pbgui_vpin::vpin_selector_widget::{LevelMap, VpinSelectorWidget};
+use qt_core::Slot;
+use qt_widgets::{QApplication, QHBoxLayout, QListWidget, QPushButton, QVBoxLayout, QWidget};
+
+fn main() {
+    QApplication::init(|_app| unsafe {
+        let mut main = QWidget::new_0a();
+        let mut layout = QHBoxLayout::new_0a();
+        let mut layout_ptr = layout.as_mut_ptr();
+        main.set_layout(layout.into_ptr());
+        // stand in for the package browser
+        layout_ptr.add_widget(QListWidget::new_0a().into_ptr());
+
+        // the side panel hosts the selector along with a button to report the selection
+        let mut panel = QWidget::new_0a();
+        let mut panel_layout = QVBoxLayout::new_0a();
+        let mut panel_layout_ptr = panel_layout.as_mut_ptr();
+        panel.set_layout(panel_layout.into_ptr());
+
+        let selector =
+            VpinSelectorWidget::create("DEV01", "modelpublish-1.2.0", panel.as_mut_ptr());
+        selector.set_roles(vec!["anim", "model", "fx", "light"]);
+        selector.set_sites(vec!["montreal", "vancouver"]);
+        let mut levels = LevelMap::new();
+        levels.insert(
+            "RD".to_string(),
+            vec!["0001".to_string(), "0002".to_string()],
+        );
+        selector.set_levels(levels);
+        panel_layout_ptr.add_widget(selector.widget());
+
+        let mut button = QPushButton::from_q_string(&qt_core::QString::from_std_str("Pin"));
+        let button_ptr = button.as_mut_ptr();
+        panel_layout_ptr.add_widget(button.into_ptr());
+        layout_ptr.add_widget(panel.into_ptr());
+
+        let pin_slot = Slot::new(|| {
+            println!("{:?}", selector.selection());
+        });
+        button_ptr.clicked().connect(&pin_slot);
+
+        main.show();
+        QApplication::exec()
+    });
+}
@@ -0,0 +1,29 @@
+use pbgui_vpin::vpin_dialog::LevelMap;
+use pbgui_vpin::{prompt, PromptOptions};
+use qt_widgets::QApplication;
+
+fn main() {
+    QApplication::init(|_app| unsafe {
+        let mut levels = LevelMap::new();
+        levels.insert(
+            "RD".to_string(),
+            vec!["0001".to_string(), "0002".to_string()],
+        );
+        let options = PromptOptions {
+            roles: vec!["anim".to_string(), "model".to_string(), "fx".to_string()],
+            sites: vec!["montreal".to_string(), "vancouver".to_string()],
+            levels,
+            ..PromptOptions::default()
+        };
+        match prompt("DEV01", "modelpublish-1.2.0", options) {
+            Some(selection) => {
+                println!("{:?}", selection);
+                0
+            }
+            None => {
+                println!("cancelled");
+                1
+            }
+        }
+    });
+}
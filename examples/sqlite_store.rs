@@ -0,0 +1,39 @@
+use pbgui_vpin::sqlite_store::SqliteStore;
+use pbgui_vpin::vpin_dialog::{LevelMap, VpinDialog};
+use qt_widgets::{cpp_core::MutPtr, QApplication};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "pins.db".to_string());
+    let store = match SqliteStore::open(path.as_str()) {
+        Ok(store) => Rc::new(RefCell::new(store)),
+        Err(err) => {
+            eprintln!("unable to open {}: {}", path, err);
+            std::process::exit(2);
+        }
+    };
+    QApplication::init(|_app| unsafe {
+        let dialog = VpinDialog::create("DEV01", "modelpublish-1.2.0", MutPtr::null());
+        dialog.set_default_stylesheet();
+        dialog.set_roles(vec!["anim", "model", "fx"]);
+        dialog.set_sites(vec!["montreal", "vancouver"]);
+        let mut levels = LevelMap::new();
+        levels.insert(
+            "RD".to_string(),
+            vec!["0001".to_string(), "0002".to_string()],
+        );
+        dialog.set_levels(levels);
+        // the pins in the store are reported as conflicts, and the accepted pins
+        // are written back to it
+        if let Err(err) = dialog.load_existing_pins(&mut *store.borrow_mut()) {
+            eprintln!("unable to load pins from {}: {}", path, err);
+            return 2;
+        }
+        dialog.commit_to(store.clone());
+        dialog.dialog_mut().exec();
+        0
+    });
+}
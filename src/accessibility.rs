@@ -0,0 +1,88 @@
+//! Helpers for making widgets accessible to screen readers and keyboard users.
+use qt_core::FocusPolicy;
+use qt_widgets::{
+    cpp_core::{CastInto, MutPtr, Ptr},
+    QWidget,
+};
+use rustqt_utils::qs;
+
+/// Set the accessible name and description of a widget, which are announced
+/// by screen readers
+///
+/// # Arguments
+/// * `widget` - The widget to label
+/// * `name` - A short name for the widget
+/// * `description` - A sentence describing what the widget does
+///
+/// # Returns
+/// * None
+pub unsafe fn set_accessible(
+    widget: impl CastInto<MutPtr<QWidget>>,
+    name: &str,
+    description: &str,
+) {
+    let mut widget = widget.cast_into();
+    widget.set_accessible_name(&qs(name));
+    widget.set_accessible_description(&qs(description));
+}
+
+/// Chain the tab order of the supplied widgets, in the order supplied
+///
+/// # Arguments
+/// * `widgets` - The widgets in the desired tab order
+///
+/// # Returns
+/// * None
+pub unsafe fn set_tab_chain(widgets: &[MutPtr<QWidget>]) {
+    for pair in widgets.windows(2) {
+        QWidget::set_tab_order(pair[0], pair[1]);
+    }
+}
+
+/// Find the widgets beneath the root which accept keyboard focus, walking the
+/// whole widget tree. The children of a focusable widget are parts of it (eg the
+/// line edit within a date edit), and are announced through it, so they are not
+/// searched.
+///
+/// # Arguments
+/// * `root` - The widget whose descendants are searched
+///
+/// # Returns
+/// * Vector of the focusable widgets
+pub unsafe fn focusable_widgets(root: impl CastInto<Ptr<QWidget>>) -> Vec<Ptr<QWidget>> {
+    let mut widgets = Vec::new();
+    let mut parents = vec![root.cast_into()];
+    while let Some(parent) = parents.pop() {
+        let children = parent.children();
+        let mut nested = Vec::new();
+        for idx in 0..children.length() {
+            let widget: MutPtr<QWidget> = (*children.at(idx)).dynamic_cast_mut();
+            if widget.is_null() {
+                continue;
+            }
+            let widget = widget.as_ptr();
+            if widget.focus_policy() == FocusPolicy::NoFocus {
+                nested.push(widget);
+            } else {
+                widgets.push(widget);
+            }
+        }
+        parents.extend(nested);
+    }
+    widgets
+}
+
+/// Identify the focusable widgets beneath the root which lack an accessible name
+///
+/// # Arguments
+/// * `root` - The widget whose descendants are checked
+///
+/// # Returns
+/// * Vector of the object names of the widgets lacking an accessible name
+pub unsafe fn unlabelled_widgets(root: impl CastInto<Ptr<QWidget>>) -> Vec<String> {
+    focusable_widgets(root)
+        .iter()
+        .filter(|widget| widget.accessible_name().is_empty())
+        .map(|widget| widget.object_name().to_std_string())
+        .collect()
+}
@@ -0,0 +1,451 @@
+//! Command line companion to the VpinDialog. It presents the dialog and prints the
+//! accepted selection, or the pins it expands to, as JSON on stdout, so that scripts
+//! may reuse the dialog without linking against Rust.
+//!
+//! The selection lists its distributions under `distributions`. Earlier versions
+//! printed a single `distribution` string instead, so scripts parsing the output
+//! must read the list. A `distribution` string is still accepted in the files read
+//! in `--batch` mode.
+//!
+//! In `--batch` mode, no window is opened. Instead, a VpinSelection is read from a
+//! JSON or TOML file, validated against the supplied roles, levels, sites and
+//! platforms exactly as the dialog would, and the expanded pins are printed. Conflicts
+//! with the pins supplied via `--existing-pins`, and any warnings about the
+//! selection, are reported on stderr.
+//!
+//! `--require-comment`, `--require-ticket` and `--ticket-pattern` apply the same
+//! comment and ticket rules in both modes.
+//!
+//! With the `sqlite` feature, `--db` names a local pin store: the pins already in
+//! place for the show are loaded from it, and the accepted pins are written to it.
+//! The `pins` subcommand lists and deletes the pins in such a store.
+use pbgui_vpin::conflict::detect_conflicts;
+use pbgui_vpin::i18n::Catalog;
+#[cfg(feature = "sqlite")]
+use pbgui_vpin::pin_provider::PinProvider;
+#[cfg(feature = "sqlite")]
+use pbgui_vpin::pin_sink::write_pins;
+#[cfg(feature = "sqlite")]
+use pbgui_vpin::sqlite_store::{PinQuery, SqliteStore};
+use pbgui_vpin::validation::{ticket_regex, warnings, SelectionContext};
+use pbgui_vpin::vpin_dialog::{LevelMap, PinSpec, VpinSelection};
+use pbgui_vpin::{prompt, PromptOptions};
+use qt_widgets::QApplication;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+
+/// Exit code when the user accepts the dialog, or the batch selection is valid
+const EXIT_ACCEPTED: i32 = 0;
+/// Exit code when the user cancels the dialog
+const EXIT_CANCELLED: i32 = 1;
+/// Exit code when an error prevents us from presenting the dialog or reporting the result
+const EXIT_ERROR: i32 = 2;
+/// Exit code when the batch selection fails validation
+const EXIT_INVALID: i32 = 3;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "pbgui-vpin",
+    about = "Prompt for a version pin and print the selection as JSON",
+    after_help = "The printed selection lists its distributions under `distributions`. \
+                  Earlier versions printed a single `distribution` string instead. A \
+                  `distribution` string is still accepted in --batch files.",
+    setting = structopt::clap::AppSettings::SubcommandsNegateReqs
+)]
+struct Opt {
+    /// The name of the show
+    #[structopt(long, required_unless = "batch")]
+    show: Option<String>,
+    /// Comma separated list of distributions we are setting pins for
+    /// (eg modelpublish-1.2.0,modelpublish-plugin-1.2.0)
+    #[structopt(long, use_delimiter = true, required_unless = "batch")]
+    distribution: Vec<String>,
+    /// Comma separated list of roles to choose from
+    #[structopt(long, use_delimiter = true)]
+    roles: Vec<String>,
+    /// Comma separated list of sites to choose from
+    #[structopt(long, use_delimiter = true)]
+    sites: Vec<String>,
+    /// Comma separated list of platforms to choose from (eg linux-el7,linux-el9)
+    #[structopt(long, use_delimiter = true)]
+    platforms: Vec<String>,
+    /// JSON file mapping each sequence to a list of shots
+    #[structopt(long, parse(from_os_str))]
+    levels_file: Option<PathBuf>,
+    /// JSON file listing the pins already in place, against which conflicts are reported
+    #[structopt(long, parse(from_os_str))]
+    existing_pins: Option<PathBuf>,
+    /// Print the expanded list of pins rather than the selection
+    #[structopt(long)]
+    expand: bool,
+    /// Validate the selection in the supplied JSON or TOML file and print the
+    /// expanded pins, without opening a window
+    #[structopt(long, parse(from_os_str))]
+    batch: Option<PathBuf>,
+    /// Require a comment explaining why the pins are needed
+    #[structopt(long)]
+    require_comment: bool,
+    /// Require a ticket tracking the pins
+    #[structopt(long)]
+    require_ticket: bool,
+    /// Regular expression the whole ticket must match (eg '[A-Z]+-[0-9]+')
+    #[structopt(long)]
+    ticket_pattern: Option<String>,
+    /// SQLite pin store from which the existing pins are loaded, and to which the
+    /// accepted pins are written
+    #[cfg(feature = "sqlite")]
+    #[structopt(long, parse(from_os_str))]
+    db: Option<PathBuf>,
+    #[cfg(feature = "sqlite")]
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[cfg(feature = "sqlite")]
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// List or delete the pins in a SQLite pin store
+    Pins {
+        /// The SQLite pin store
+        #[structopt(long, parse(from_os_str))]
+        db: PathBuf,
+        #[structopt(subcommand)]
+        action: PinsAction,
+    },
+}
+
+#[cfg(feature = "sqlite")]
+#[derive(Debug, StructOpt)]
+enum PinsAction {
+    /// Print the pins matching the filters as JSON
+    List {
+        /// Only list the pins for this show
+        #[structopt(long)]
+        show: Option<String>,
+        /// Only list the pins at this level (eg DEV01.RD.0001)
+        #[structopt(long)]
+        level: Option<String>,
+        /// Only list the pins for this role
+        #[structopt(long)]
+        role: Option<String>,
+        /// Only list the pins for this site
+        #[structopt(long)]
+        site: Option<String>,
+        /// Only list the pins for this platform
+        #[structopt(long)]
+        platform: Option<String>,
+    },
+    /// Delete the pins with the supplied ids
+    Delete {
+        /// The ids of the pins, as printed by `list`
+        #[structopt(required = true)]
+        ids: Vec<i64>,
+    },
+}
+
+fn main() {
+    // clap exits with 1 on a usage error, which we reserve for cancellation
+    let opt = match Opt::from_args_safe() {
+        Ok(opt) => opt,
+        Err(err) if err.use_stderr() => {
+            eprintln!("{}", err);
+            std::process::exit(EXIT_ERROR);
+        }
+        Err(err) => err.exit(),
+    };
+    // the strings fall back to the locale's catalog, but the user should know why
+    if let Err(err) = Catalog::from_env() {
+        eprintln!("warning: {}", err);
+    }
+    #[cfg(feature = "sqlite")]
+    {
+        if let Some(Command::Pins { db, action }) = &opt.command {
+            std::process::exit(run_pins(db, action));
+        }
+    }
+    if let Some(pattern) = &opt.ticket_pattern {
+        if let Err(err) = ticket_regex(pattern.as_str()) {
+            eprintln!("{}", err);
+            std::process::exit(EXIT_ERROR);
+        }
+    }
+    let levels = match &opt.levels_file {
+        Some(path) => match read_levels(path) {
+            Ok(levels) => levels,
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(EXIT_ERROR);
+            }
+        },
+        None => LevelMap::new(),
+    };
+    #[allow(unused_mut)]
+    let mut existing_pins = match &opt.existing_pins {
+        Some(path) => match read_pins(path) {
+            Ok(pins) => pins,
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(EXIT_ERROR);
+            }
+        },
+        None => Vec::new(),
+    };
+    if let Some(batch) = &opt.batch {
+        let context = SelectionContext {
+            distributions: opt.distribution.clone(),
+            roles: opt.roles.clone(),
+            levels,
+            sites: opt.sites.clone(),
+            platforms: opt.platforms.clone(),
+            comment_required: opt.require_comment,
+            ticket_required: opt.require_ticket,
+            ticket_pattern: opt.ticket_pattern.clone(),
+        };
+        std::process::exit(run_batch(batch, &context, &existing_pins));
+    }
+    // structopt guarantees these are present outside of batch mode
+    let show = opt.show.clone().unwrap_or_default();
+    let distribution = opt.distribution.first().cloned().unwrap_or_default();
+    #[cfg(feature = "sqlite")]
+    let mut store = match &opt.db {
+        Some(db) => match open_store(db) {
+            Ok(mut store) => match store.existing_pins(show.as_str()) {
+                Ok(pins) => {
+                    existing_pins.extend(pins);
+                    Some(store)
+                }
+                Err(err) => {
+                    eprintln!("unable to read pins from {}: {}", db.display(), err);
+                    std::process::exit(EXIT_ERROR);
+                }
+            },
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(EXIT_ERROR);
+            }
+        },
+        None => None,
+    };
+    let options = PromptOptions {
+        distributions: opt.distribution.clone(),
+        roles: opt.roles.clone(),
+        sites: opt.sites.clone(),
+        platforms: opt.platforms.clone(),
+        levels,
+        existing_pins,
+        comment_required: opt.require_comment,
+        ticket_required: opt.require_ticket,
+        ticket_pattern: opt.ticket_pattern.clone(),
+        ..PromptOptions::default()
+    };
+    QApplication::init(|_app| unsafe {
+        let selection = match prompt(show.as_str(), distribution.as_str(), options) {
+            Some(selection) => selection,
+            None => return EXIT_CANCELLED,
+        };
+        #[cfg(feature = "sqlite")]
+        {
+            if let Some(store) = store.as_mut() {
+                if let Err(err) = write_pins(store, &selection.expand()) {
+                    eprintln!("{}", err);
+                    return EXIT_ERROR;
+                }
+            }
+        }
+        let output = if opt.expand {
+            serde_json::to_string_pretty(&selection.expand())
+        } else {
+            serde_json::to_string_pretty(&selection)
+        };
+        print_output(output)
+    });
+}
+
+// Read, validate and expand the selection stored in the supplied file, reporting
+// conflicts with the existing pins, and returning the exit code
+fn run_batch(path: &Path, context: &SelectionContext, existing_pins: &[PinSpec]) -> i32 {
+    let selection = match read_selection(path) {
+        Ok(selection) => selection,
+        Err(err) => {
+            eprintln!("{}", err);
+            return EXIT_ERROR;
+        }
+    };
+    if let Err(err) = context.validate(&selection) {
+        eprintln!("{}", err);
+        return EXIT_INVALID;
+    }
+    for warning in warnings(&selection) {
+        eprintln!("warning: {}", warning);
+    }
+    let pins = selection.expand();
+    for conflict in detect_conflicts(&pins, existing_pins) {
+        eprintln!("warning: {}", conflict);
+    }
+    print_output(serde_json::to_string_pretty(&pins))
+}
+
+// List or delete the pins in the store, returning the exit code
+#[cfg(feature = "sqlite")]
+fn run_pins(db: &Path, action: &PinsAction) -> i32 {
+    let store = match open_store(db) {
+        Ok(store) => store,
+        Err(err) => {
+            eprintln!("{}", err);
+            return EXIT_ERROR;
+        }
+    };
+    match action {
+        PinsAction::List {
+            show,
+            level,
+            role,
+            site,
+            platform,
+        } => {
+            let query = PinQuery {
+                show: show.clone(),
+                level: level.clone(),
+                role: role.clone(),
+                site: site.clone(),
+                platform: platform.clone(),
+            };
+            match store.query(&query) {
+                Ok(pins) => print_output(serde_json::to_string_pretty(&pins)),
+                Err(err) => {
+                    eprintln!("unable to list pins in {}: {}", db.display(), err);
+                    EXIT_ERROR
+                }
+            }
+        }
+        PinsAction::Delete { ids } => {
+            let mut code = EXIT_ACCEPTED;
+            for id in ids {
+                match store.delete(*id) {
+                    Ok(true) => (),
+                    Ok(false) => {
+                        eprintln!("no pin with id {}", id);
+                        code = EXIT_ERROR;
+                    }
+                    Err(err) => {
+                        eprintln!("unable to delete pin {}: {}", id, err);
+                        return EXIT_ERROR;
+                    }
+                }
+            }
+            code
+        }
+    }
+}
+
+// Open the SQLite pin store, migrating its schema
+#[cfg(feature = "sqlite")]
+fn open_store(db: &Path) -> Result<SqliteStore, String> {
+    SqliteStore::open(db).map_err(|err| format!("unable to open {}: {}", db.display(), err))
+}
+
+// Print the serialized output, returning the exit code
+fn print_output(output: serde_json::Result<String>) -> i32 {
+    match output {
+        Ok(output) => {
+            println!("{}", output);
+            EXIT_ACCEPTED
+        }
+        Err(err) => {
+            eprintln!("unable to serialize selection: {}", err);
+            EXIT_ERROR
+        }
+    }
+}
+
+// Read a LevelMap from a JSON file whose keys are sequences and whose values
+// are lists of shots
+fn read_levels(path: &Path) -> Result<LevelMap, String> {
+    let file =
+        File::open(path).map_err(|err| format!("unable to open {}: {}", path.display(), err))?;
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(|err| format!("unable to parse {}: {}", path.display(), err))
+}
+
+// Read a list of PinSpecs from a JSON file
+fn read_pins(path: &Path) -> Result<Vec<PinSpec>, String> {
+    let file =
+        File::open(path).map_err(|err| format!("unable to open {}: {}", path.display(), err))?;
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(|err| format!("unable to parse {}: {}", path.display(), err))
+}
+
+// Read a VpinSelection from a TOML file, if the extension is `toml`, or from
+// a JSON file otherwise
+fn read_selection(path: &Path) -> Result<VpinSelection, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("unable to read {}: {}", path.display(), err))?;
+    let is_toml = path.extension().map(|ext| ext == "toml").unwrap_or(false);
+    if is_toml {
+        toml::from_str(contents.as_str())
+            .map_err(|err| format!("unable to parse {}: {}", path.display(), err))
+    } else {
+        serde_json::from_str(contents.as_str())
+            .map_err(|err| format!("unable to parse {}: {}", path.display(), err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Write the contents to a file named after the test in the temp directory,
+    // returning its path
+    fn selection_file(name: &str, contents: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("pbgui-vpin-batch-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).expect("unable to write the selection file");
+        path
+    }
+
+    fn context() -> SelectionContext {
+        SelectionContext {
+            distributions: vec!["modelpublish-1.2.0".to_string()],
+            roles: vec!["anim".to_string()],
+            ..SelectionContext::default()
+        }
+    }
+
+    #[test]
+    fn batch_accepts_a_valid_json_or_toml_selection() {
+        let json = selection_file(
+            "valid.json",
+            r#"{"show": "DEV01", "distribution": "modelpublish-1.2.0",
+                "roles": ["anim"], "level": null, "site": null}"#,
+        );
+        let toml = selection_file(
+            "valid.toml",
+            "show = \"DEV01\"\ndistributions = [\"modelpublish-1.2.0\"]\n",
+        );
+        assert_eq!(run_batch(&json, &context(), &[]), EXIT_ACCEPTED);
+        assert_eq!(run_batch(&toml, &context(), &[]), EXIT_ACCEPTED);
+        fs::remove_file(json).ok();
+        fs::remove_file(toml).ok();
+    }
+
+    #[test]
+    fn batch_reports_an_invalid_selection() {
+        let path = selection_file(
+            "invalid.json",
+            r#"{"show": "DEV01", "distribution": "modelpublish-1.2.0",
+                "roles": ["lighting"], "level": null, "site": null}"#,
+        );
+        assert_eq!(run_batch(&path, &context(), &[]), EXIT_INVALID);
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn batch_reports_an_unreadable_selection_as_an_error() {
+        let path = selection_file("malformed.json", "{\"show\": ");
+        assert_eq!(run_batch(&path, &context(), &[]), EXIT_ERROR);
+        fs::remove_file(&path).ok();
+        assert_eq!(run_batch(&path, &context(), &[]), EXIT_ERROR);
+    }
+}
@@ -0,0 +1,422 @@
+//! A caching layer around a DataProvider, so that opening the dialog repeatedly
+//! for the same show does not re-fetch the same roles, levels, sites and
+//! platforms. Each entry expires after a time to live, which may be set per key.
+//! The cache may be backed by a file, so that a fresh process starts warm.
+//!
+//! The existing pins are never cached, as conflicts must be detected against the
+//! pins currently in place.
+use crate::data_provider::DataProvider;
+use crate::pin_provider::PinProvider;
+use crate::pin_sink::SinkError;
+use crate::pin_spec::PinSpec;
+use crate::vpin_selection::LevelMap;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// The default time to live of a cache entry
+pub const DEFAULT_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// CacheKey identifies a DataProvider request
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CacheKey {
+    /// The roles
+    Roles,
+    /// The sites
+    Sites,
+    /// The platforms
+    Platforms,
+    /// The sequences and shots of a show
+    Levels(String),
+    /// The distributions of a package
+    Distributions(String),
+}
+
+/// CacheStats counts the requests served from the cache, and those passed on to
+/// the provider
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Requests served from the cache
+    pub hits: u64,
+    /// Requests passed on to the provider, because the entry was missing or expired
+    pub misses: u64,
+}
+
+// A cached response, along with the time at which it was retrieved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    value: serde_json::Value,
+    stored_at: SystemTime,
+}
+
+/// CachingProvider wraps a DataProvider, serving repeated requests from a cache
+pub struct CachingProvider<P> {
+    provider: P,
+    default_ttl: Duration,
+    ttls: HashMap<CacheKey, Duration>,
+    entries: HashMap<CacheKey, Entry>,
+    path: Option<PathBuf>,
+    stats: CacheStats,
+}
+
+impl<P> CachingProvider<P> {
+    /// Wrap the provider with an empty, in memory cache, whose entries live for
+    /// `DEFAULT_TTL`
+    ///
+    /// # Arguments
+    /// * `provider` - The DataProvider to cache
+    ///
+    /// # Returns
+    /// * CachingProvider instance
+    pub fn new(provider: P) -> Self {
+        CachingProvider {
+            provider,
+            default_ttl: DEFAULT_TTL,
+            ttls: HashMap::new(),
+            entries: HashMap::new(),
+            path: None,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Back the cache with a file. Any entries in the file are loaded, and the file
+    /// is rewritten whenever an entry is stored or invalidated. A missing or
+    /// unreadable file leaves the cache empty.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the cache file
+    ///
+    /// # Returns
+    /// * The CachingProvider
+    pub fn with_cache_file<T: AsRef<Path>>(mut self, path: T) -> Self {
+        let path = path.as_ref().to_path_buf();
+        if let Ok(contents) = fs::read_to_string(&path) {
+            if let Ok(entries) = serde_json::from_str::<Vec<(CacheKey, Entry)>>(&contents) {
+                self.entries.extend(entries);
+            }
+        }
+        self.path = Some(path);
+        self
+    }
+
+    /// Set the time to live of the entries without a time to live of their own
+    ///
+    /// # Arguments
+    /// * `ttl` - The time to live
+    ///
+    /// # Returns
+    /// * The CachingProvider
+    pub fn with_default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// Set the time to live of an entry
+    ///
+    /// # Arguments
+    /// * `key` - The CacheKey of the entry
+    /// * `ttl` - The time to live
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_ttl(&mut self, key: CacheKey, ttl: Duration) {
+        self.ttls.insert(key, ttl);
+    }
+
+    /// Retrieve the counts of cache hits and misses
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * CacheStats instance
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Discard an entry, so that the next request for it reaches the provider
+    ///
+    /// # Arguments
+    /// * `key` - The CacheKey of the entry
+    ///
+    /// # Returns
+    /// * Ok, or the error writing the cache file
+    pub fn invalidate(&mut self, key: &CacheKey) -> io::Result<()> {
+        if self.entries.remove(key).is_some() {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Discard every entry
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Ok, or the error writing the cache file
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.entries.clear();
+        self.save()
+    }
+
+    /// Retrieve the wrapped provider
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Mutable reference to the provider
+    pub fn provider_mut(&mut self) -> &mut P {
+        &mut self.provider
+    }
+
+    // serve the request from the cache if the entry is fresh, otherwise pass it on
+    // to the provider and store the response
+    fn cached<T, F>(&mut self, key: CacheKey, request: F) -> Result<T, SinkError>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce(&mut P) -> Result<T, SinkError>,
+    {
+        let ttl = self.ttls.get(&key).cloned().unwrap_or(self.default_ttl);
+        if let Some(entry) = self.entries.get(&key) {
+            let age = entry.stored_at.elapsed().unwrap_or(Duration::from_secs(0));
+            if age < ttl {
+                if let Ok(value) = serde_json::from_value(entry.value.clone()) {
+                    self.stats.hits += 1;
+                    return Ok(value);
+                }
+            }
+        }
+        self.stats.misses += 1;
+        let value = request(&mut self.provider)?;
+        let entry = Entry {
+            value: serde_json::to_value(&value)?,
+            stored_at: SystemTime::now(),
+        };
+        self.entries.insert(key, entry);
+        // a cache which cannot be written is still of use in memory
+        let _ = self.save();
+        Ok(value)
+    }
+
+    // write the entries to the cache file, if there is one
+    fn save(&self) -> io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let entries = self.entries.iter().collect::<Vec<_>>();
+        let contents = serde_json::to_string(&entries)?;
+        fs::write(path, contents)
+    }
+}
+
+impl<P: DataProvider> DataProvider for CachingProvider<P> {
+    fn roles(&mut self) -> Result<Vec<String>, SinkError> {
+        self.cached(CacheKey::Roles, |provider| provider.roles())
+    }
+
+    fn levels(&mut self, show: &str) -> Result<LevelMap, SinkError> {
+        self.cached(CacheKey::Levels(show.to_string()), |provider| {
+            provider.levels(show)
+        })
+    }
+
+    fn sites(&mut self) -> Result<Vec<String>, SinkError> {
+        self.cached(CacheKey::Sites, |provider| provider.sites())
+    }
+
+    fn platforms(&mut self) -> Result<Vec<String>, SinkError> {
+        self.cached(CacheKey::Platforms, |provider| provider.platforms())
+    }
+
+    fn distributions(&mut self, package: &str) -> Result<Vec<String>, SinkError> {
+        self.cached(CacheKey::Distributions(package.to_string()), |provider| {
+            provider.distributions(package)
+        })
+    }
+}
+
+// the existing pins pass straight through to the provider
+impl<P: PinProvider> PinProvider for CachingProvider<P> {
+    fn existing_pins(&mut self, show: &str) -> Result<Vec<PinSpec>, SinkError> {
+        self.provider.existing_pins(show)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // CountingProvider records each request which reaches it
+    #[derive(Default)]
+    struct CountingProvider {
+        requests: Vec<String>,
+        fail: bool,
+    }
+
+    impl CountingProvider {
+        fn request(&mut self, request: &str) -> Result<(), SinkError> {
+            self.requests.push(request.to_string());
+            if self.fail {
+                return Err(format!("unable to retrieve the {}", request).into());
+            }
+            Ok(())
+        }
+    }
+
+    impl DataProvider for CountingProvider {
+        fn roles(&mut self) -> Result<Vec<String>, SinkError> {
+            self.request("roles")?;
+            Ok(vec!["anim".to_string(), "model".to_string()])
+        }
+
+        fn levels(&mut self, show: &str) -> Result<LevelMap, SinkError> {
+            self.request(show)?;
+            let mut levels = LevelMap::new();
+            levels.insert("RD".to_string(), vec!["0001".to_string()]);
+            Ok(levels)
+        }
+
+        fn sites(&mut self) -> Result<Vec<String>, SinkError> {
+            self.request("sites")?;
+            Ok(vec!["hyderabad".to_string()])
+        }
+
+        fn distributions(&mut self, package: &str) -> Result<Vec<String>, SinkError> {
+            self.request(package)?;
+            Ok(vec![format!("{}-1.2.0", package)])
+        }
+    }
+
+    fn cache_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "pbgui-vpin-cache-{}-{}.json",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn serves_repeated_requests_from_the_cache() {
+        let mut cache = CachingProvider::new(CountingProvider::default());
+        assert_eq!(cache.roles().unwrap(), vec!["anim", "model"]);
+        assert_eq!(cache.roles().unwrap(), vec!["anim", "model"]);
+        assert_eq!(cache.levels("DEV01").unwrap().len(), 1);
+        assert_eq!(cache.levels("DEV01").unwrap().len(), 1);
+        cache.levels("DEV02").unwrap();
+        assert_eq!(
+            cache.distributions("modelpublish").unwrap(),
+            vec!["modelpublish-1.2.0"]
+        );
+        assert_eq!(
+            cache.provider_mut().requests,
+            vec!["roles", "DEV01", "DEV02", "modelpublish"]
+        );
+        assert_eq!(cache.stats(), CacheStats { hits: 2, misses: 4 });
+    }
+
+    #[test]
+    fn passes_expired_entries_on_to_the_provider() {
+        let mut cache = CachingProvider::new(CountingProvider::default())
+            .with_default_ttl(Duration::from_secs(0));
+        cache.roles().unwrap();
+        cache.roles().unwrap();
+        assert_eq!(cache.provider_mut().requests, vec!["roles", "roles"]);
+        assert_eq!(cache.stats(), CacheStats { hits: 0, misses: 2 });
+    }
+
+    #[test]
+    fn applies_the_time_to_live_of_each_key() {
+        let mut cache = CachingProvider::new(CountingProvider::default())
+            .with_default_ttl(Duration::from_secs(0));
+        cache.set_ttl(CacheKey::Roles, Duration::from_secs(60));
+        cache.roles().unwrap();
+        cache.roles().unwrap();
+        cache.sites().unwrap();
+        cache.sites().unwrap();
+        assert_eq!(
+            cache.provider_mut().requests,
+            vec!["roles", "sites", "sites"]
+        );
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 3 });
+    }
+
+    #[test]
+    fn does_not_cache_failures() {
+        let mut cache = CachingProvider::new(CountingProvider {
+            fail: true,
+            ..CountingProvider::default()
+        });
+        assert!(cache.roles().is_err());
+        cache.provider_mut().fail = false;
+        assert!(cache.roles().is_ok());
+        assert_eq!(cache.stats(), CacheStats { hits: 0, misses: 2 });
+    }
+
+    #[test]
+    fn invalidates_and_clears_entries() {
+        let mut cache = CachingProvider::new(CountingProvider::default());
+        cache.roles().unwrap();
+        cache.sites().unwrap();
+        cache.invalidate(&CacheKey::Roles).unwrap();
+        cache.roles().unwrap();
+        cache.sites().unwrap();
+        assert_eq!(
+            cache.provider_mut().requests,
+            vec!["roles", "sites", "roles"]
+        );
+        cache.clear().unwrap();
+        cache.roles().unwrap();
+        cache.sites().unwrap();
+        assert_eq!(
+            cache.provider_mut().requests,
+            vec!["roles", "sites", "roles", "roles", "sites"]
+        );
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 5 });
+    }
+
+    #[test]
+    fn loads_the_entries_stored_in_the_cache_file() {
+        let path = cache_file("round-trip");
+        let mut cache = CachingProvider::new(CountingProvider::default()).with_cache_file(&path);
+        cache.roles().unwrap();
+        cache.levels("DEV01").unwrap();
+        cache.sites().unwrap();
+        cache.invalidate(&CacheKey::Sites).unwrap();
+
+        let mut warm = CachingProvider::new(CountingProvider::default()).with_cache_file(&path);
+        assert_eq!(warm.roles().unwrap(), vec!["anim", "model"]);
+        assert_eq!(warm.levels("DEV01").unwrap()["RD"], vec!["0001"]);
+        warm.sites().unwrap();
+        assert_eq!(warm.provider_mut().requests, vec!["sites"]);
+        assert_eq!(warm.stats(), CacheStats { hits: 2, misses: 1 });
+
+        warm.clear().unwrap();
+        let mut cleared = CachingProvider::new(CountingProvider::default()).with_cache_file(&path);
+        cleared.roles().unwrap();
+        assert_eq!(cleared.stats(), CacheStats { hits: 0, misses: 1 });
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn starts_empty_from_an_unreadable_cache_file() {
+        let path = cache_file("unreadable");
+        fs::write(&path, "not json").unwrap();
+        let mut cache = CachingProvider::new(CountingProvider::default()).with_cache_file(&path);
+        cache.roles().unwrap();
+        assert_eq!(cache.stats(), CacheStats { hits: 0, misses: 1 });
+        // the file is rewritten with the entry retrieved
+        let mut warm = CachingProvider::new(CountingProvider::default()).with_cache_file(&path);
+        warm.roles().unwrap();
+        assert_eq!(warm.stats(), CacheStats { hits: 1, misses: 0 });
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,82 @@
+//! A Choice is the value behind an entry in one of the dialog's comboboxes. Entries
+//! are identified by their Choice rather than their display text, so that a sequence
+//! named "All Sequences" or a site named "any" is never mistaken for the wildcard.
+
+/// Choice is either the wildcard entry, or a specific identifier
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum Choice {
+    /// Any value is acceptable
+    #[default]
+    Any,
+    /// The identifier of a specific sequence, shot or site
+    Specific(String),
+}
+
+impl From<Option<String>> for Choice {
+    fn from(value: Option<String>) -> Self {
+        match value {
+            Some(value) => Choice::Specific(value),
+            None => Choice::Any,
+        }
+    }
+}
+
+impl From<Choice> for Option<String> {
+    fn from(choice: Choice) -> Self {
+        choice.into_option()
+    }
+}
+
+impl Choice {
+    /// Create a Choice for a specific identifier
+    ///
+    /// # Arguments
+    /// * `id` - The identifier
+    ///
+    /// # Returns
+    /// * Choice::Specific instance
+    pub fn specific<I: Into<String>>(id: I) -> Self {
+        Choice::Specific(id.into())
+    }
+
+    /// Determine whether this is the wildcard entry
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * true if the Choice is `Any`
+    pub fn is_any(&self) -> bool {
+        *self == Choice::Any
+    }
+
+    /// Retrieve the identifier of a specific Choice
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some identifier for `Specific`
+    /// * None for `Any`
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Choice::Any => None,
+            Choice::Specific(id) => Some(id.as_str()),
+        }
+    }
+
+    /// Convert the Choice into the Option used by VpinSelection
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some identifier for `Specific`
+    /// * None for `Any`
+    pub fn into_option(self) -> Option<String> {
+        match self {
+            Choice::Any => None,
+            Choice::Specific(id) => Some(id),
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Detection of conflicts between the pins a selection expands to and the pins
+//! already in place. Pins conflict when one covers the other (see `PinSpec::covers`),
+//! regardless of the versions they pin: the more specific pin takes precedence
+//! wherever both apply.
+use crate::i18n::trf;
+use crate::pin_spec::PinSpec;
+use std::fmt;
+
+/// ConflictKind describes how a new pin relates to an existing one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConflictKind {
+    /// The new pin has the same target as the existing pin (see `PinSpec::same_target`)
+    Duplicate,
+    /// The new pin is more specific than the existing pin, and overrides it
+    /// within the new pin's scope
+    Shadows,
+    /// The existing pin is more specific than the new pin, so the new pin has
+    /// no effect within the existing pin's scope
+    ShadowedBy,
+}
+
+/// Conflict pairs a new pin with an existing pin it conflicts with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// The new pin
+    pub pin: PinSpec,
+    /// The existing pin
+    pub existing: PinSpec,
+    /// How the new pin relates to the existing pin
+    pub kind: ConflictKind,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self.kind {
+            ConflictKind::Duplicate => trf("{} duplicates {}", &[&self.pin, &self.existing]),
+            ConflictKind::Shadows => trf("{} shadows {}", &[&self.pin, &self.existing]),
+            ConflictKind::ShadowedBy => trf("{} is shadowed by {}", &[&self.pin, &self.existing]),
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+/// Determine how a new pin relates to an existing pin
+///
+/// # Arguments
+/// * `pin` - The new PinSpec
+/// * `existing` - The existing PinSpec
+///
+/// # Returns
+/// * Some ConflictKind if one pin covers the other
+/// * None if the pins are unrelated
+pub fn conflict_kind(pin: &PinSpec, existing: &PinSpec) -> Option<ConflictKind> {
+    if pin.same_target(existing) {
+        Some(ConflictKind::Duplicate)
+    } else if existing.covers(pin) {
+        Some(ConflictKind::Shadows)
+    } else if pin.covers(existing) {
+        Some(ConflictKind::ShadowedBy)
+    } else {
+        None
+    }
+}
+
+/// Find every conflict between the new pins and the existing pins
+///
+/// # Arguments
+/// * `pins` - The new pins
+/// * `existing` - The pins already in place
+///
+/// # Returns
+/// * Vector of Conflicts, ordered by new pin and then by existing pin
+pub fn detect_conflicts(pins: &[PinSpec], existing: &[PinSpec]) -> Vec<Conflict> {
+    pins.iter()
+        .flat_map(|pin| {
+            existing.iter().filter_map(move |other| {
+                conflict_kind(pin, other).map(|kind| Conflict {
+                    pin: pin.clone(),
+                    existing: other.clone(),
+                    kind,
+                })
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pin(distribution: &str, level: &str, role: Option<&str>) -> PinSpec {
+        PinSpec {
+            distribution: distribution.to_string(),
+            level: level.to_string(),
+            role: role.map(str::to_string),
+            site: None,
+            platform: None,
+            comment: None,
+            ticket: None,
+            effective_from: None,
+            expires_on: None,
+        }
+    }
+
+    #[test]
+    fn detects_duplicates() {
+        let existing = pin("modelpublish-1.2.0", "DEV01.RD", Some("anim"));
+        assert_eq!(
+            conflict_kind(
+                &pin("modelpublish-1.2.0", "DEV01.RD", Some("anim")),
+                &existing
+            ),
+            Some(ConflictKind::Duplicate)
+        );
+    }
+
+    #[test]
+    fn detects_duplicates_differing_only_in_metadata() {
+        let existing = pin("modelpublish-1.2.0", "DEV01.RD", Some("anim"));
+        let new_pin = PinSpec {
+            comment: Some("fixes the exporter".to_string()),
+            ticket: Some("PROJ-123".to_string()),
+            ..existing.clone()
+        };
+        assert_eq!(
+            conflict_kind(&new_pin, &existing),
+            Some(ConflictKind::Duplicate)
+        );
+    }
+
+    #[test]
+    fn detects_duplicates_differing_only_in_dates() {
+        let existing = PinSpec {
+            expires_on: Some("2026-12-31".to_string()),
+            ..pin("modelpublish-1.2.0", "DEV01.RD", Some("anim"))
+        };
+        let new_pin = PinSpec {
+            effective_from: Some("2026-11-01".to_string()),
+            expires_on: Some("2027-03-31".to_string()),
+            ..existing.clone()
+        };
+        assert_eq!(
+            conflict_kind(&new_pin, &existing),
+            Some(ConflictKind::Duplicate)
+        );
+    }
+
+    #[test]
+    fn detects_pins_shadowing_existing_pins() {
+        let existing = pin("modelpublish-1.2.0", "DEV01", None);
+        assert_eq!(
+            conflict_kind(
+                &pin("modelpublish-1.3.0", "DEV01.RD", Some("anim")),
+                &existing
+            ),
+            Some(ConflictKind::Shadows)
+        );
+        // another version at the same scope overrides the existing pin
+        assert_eq!(
+            conflict_kind(&pin("modelpublish-1.3.0", "DEV01", None), &existing),
+            Some(ConflictKind::Shadows)
+        );
+    }
+
+    #[test]
+    fn detects_pins_shadowed_by_existing_pins() {
+        let existing = pin("modelpublish-1.2.0", "DEV01.RD.0001", Some("anim"));
+        assert_eq!(
+            conflict_kind(&pin("modelpublish-1.2.0", "DEV01", None), &existing),
+            Some(ConflictKind::ShadowedBy)
+        );
+    }
+
+    #[test]
+    fn ignores_unrelated_pins() {
+        let existing = pin("modelpublish-1.2.0", "DEV01.RD", Some("anim"));
+        assert_eq!(
+            conflict_kind(
+                &pin("texturepublish-1.2.0", "DEV01.RD", Some("anim")),
+                &existing
+            ),
+            None
+        );
+        assert_eq!(
+            conflict_kind(
+                &pin("modelpublish-1.2.0", "DEV01.AA", Some("anim")),
+                &existing
+            ),
+            None
+        );
+        assert_eq!(
+            conflict_kind(
+                &pin("modelpublish-1.2.0", "DEV01.RD", Some("fx")),
+                &existing
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn detects_conflicts_against_each_existing_pin() {
+        let existing = vec![
+            pin("modelpublish-1.2.0", "DEV01", None),
+            pin("modelpublish-1.2.0", "DEV01.RD", Some("anim")),
+            pin("texturepublish-1.0.0", "DEV01", None),
+        ];
+        let new_pin = pin("modelpublish-1.2.0", "DEV01.RD", Some("anim"));
+        let kinds = detect_conflicts(&[new_pin], &existing)
+            .into_iter()
+            .map(|conflict| (conflict.existing.level, conflict.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                ("DEV01".to_string(), ConflictKind::Shadows),
+                ("DEV01.RD".to_string(), ConflictKind::Duplicate),
+            ]
+        );
+    }
+}
@@ -0,0 +1,61 @@
+//! The DataProvider trait abstracts over the source of the roles, levels, sites,
+//! platforms and distributions the dialog offers, so that they may be fetched
+//! from a service rather than assembled by the caller.
+use crate::pin_sink::SinkError;
+use crate::vpin_selection::LevelMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// DataProvider is implemented by stores which can list the choices offered by the dialog
+pub trait DataProvider {
+    /// Retrieve the roles
+    fn roles(&mut self) -> Result<Vec<String>, SinkError>;
+    /// Retrieve the sequences and shots of the show
+    fn levels(&mut self, show: &str) -> Result<LevelMap, SinkError>;
+    /// Retrieve the sites
+    fn sites(&mut self) -> Result<Vec<String>, SinkError>;
+    /// Retrieve the platforms (eg `linux-el9`). Pins do not vary by platform unless
+    /// the provider offers some, so by default there are none.
+    fn platforms(&mut self) -> Result<Vec<String>, SinkError> {
+        Ok(Vec::new())
+    }
+    /// Retrieve the distributions of the package (eg `modelpublish`)
+    fn distributions(&mut self, package: &str) -> Result<Vec<String>, SinkError>;
+}
+
+// A shared provider may be moved to a worker thread while its owner keeps a handle
+// on it, eg to invalidate a cache
+impl<P: DataProvider> DataProvider for Arc<Mutex<P>> {
+    fn roles(&mut self) -> Result<Vec<String>, SinkError> {
+        lock(self)?.roles()
+    }
+
+    fn levels(&mut self, show: &str) -> Result<LevelMap, SinkError> {
+        lock(self)?.levels(show)
+    }
+
+    fn sites(&mut self) -> Result<Vec<String>, SinkError> {
+        lock(self)?.sites()
+    }
+
+    fn platforms(&mut self) -> Result<Vec<String>, SinkError> {
+        lock(self)?.platforms()
+    }
+
+    fn distributions(&mut self, package: &str) -> Result<Vec<String>, SinkError> {
+        lock(self)?.distributions(package)
+    }
+}
+
+/// Lock a shared provider, failing should a thread have panicked while holding it
+///
+/// # Arguments
+/// * `provider` - The shared provider
+///
+/// # Returns
+/// * The guard
+/// * SinkError if the lock is poisoned
+pub fn lock<P>(provider: &Mutex<P>) -> Result<MutexGuard<'_, P>, SinkError> {
+    provider
+        .lock()
+        .map_err(|_| "the provider is unavailable, as a request for it panicked".into())
+}
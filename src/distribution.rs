@@ -0,0 +1,258 @@
+//! Parsing of distribution names. A distribution is named for the package it
+//! distributes followed by its version, eg `modelpublish-1.2.0`. Pins for any
+//! version of the same package compete with one another, and their versions are
+//! compared to tell an upgrade from a downgrade.
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Split a distribution name into its package name and version. The version is
+/// the portion following the last `-` which is immediately followed by a digit.
+///
+/// # Arguments
+/// * `distribution` - The distribution name (eg `modelpublish-1.2.0`)
+///
+/// # Returns
+/// * The package name, and Some version if the name carries one
+pub fn split_distribution(distribution: &str) -> (&str, Option<&str>) {
+    let split = distribution.char_indices().rev().find(|(idx, c)| {
+        *c == '-'
+            && distribution[idx + 1..]
+                .chars()
+                .next()
+                .map(|c| c.is_ascii_digit())
+                .unwrap_or(false)
+    });
+    match split {
+        Some((idx, _)) if idx > 0 => (&distribution[..idx], Some(&distribution[idx + 1..])),
+        _ => (distribution, None),
+    }
+}
+
+/// Retrieve the package name of a distribution
+///
+/// # Arguments
+/// * `distribution` - The distribution name (eg `modelpublish-1.2.0`)
+///
+/// # Returns
+/// * The package name (eg `modelpublish`)
+pub fn package_name(distribution: &str) -> &str {
+    split_distribution(distribution).0
+}
+
+/// Version is a parsed distribution version: dotted numbers, optionally followed by
+/// a suffix marking a pre-release (eg `1.2.0`, `1.2.0-rc1`). Versions compare
+/// numerically, with missing numbers treated as zero, so `1.2` equals `1.2.0`. A
+/// pre-release sorts before the release it precedes.
+#[derive(Debug, Clone)]
+pub struct Version {
+    numbers: Vec<u64>,
+    suffix: String,
+}
+
+impl Version {
+    /// Parse a version string
+    ///
+    /// # Arguments
+    /// * `version` - The version (eg `1.2.0`)
+    ///
+    /// # Returns
+    /// * Some Version if the string starts with a number
+    /// * None otherwise
+    pub fn parse(version: &str) -> Option<Version> {
+        let mut numbers = Vec::new();
+        let mut rest = version;
+        loop {
+            let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+            if digits == 0 {
+                break;
+            }
+            numbers.push(rest[..digits].parse().ok()?);
+            rest = &rest[digits..];
+            let continues = rest.starts_with('.')
+                && rest[1..]
+                    .chars()
+                    .next()
+                    .map(|c| c.is_ascii_digit())
+                    .unwrap_or(false);
+            if !continues {
+                break;
+            }
+            rest = &rest[1..];
+        }
+        if numbers.is_empty() {
+            return None;
+        }
+        Some(Version {
+            numbers,
+            suffix: rest.trim_start_matches(['-', '.', '_']).to_string(),
+        })
+    }
+
+    /// Parse the version of a distribution
+    ///
+    /// # Arguments
+    /// * `distribution` - The distribution name (eg `modelpublish-1.2.0`)
+    ///
+    /// # Returns
+    /// * Some Version if the name carries a version
+    /// * None otherwise
+    pub fn from_distribution(distribution: &str) -> Option<Version> {
+        split_distribution(distribution).1.and_then(Version::parse)
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.numbers.len().max(other.numbers.len());
+        for idx in 0..len {
+            let mine = self.numbers.get(idx).cloned().unwrap_or(0);
+            let theirs = other.numbers.get(idx).cloned().unwrap_or(0);
+            match mine.cmp(&theirs) {
+                Ordering::Equal => continue,
+                ordering => return ordering,
+            }
+        }
+        match (self.suffix.is_empty(), other.suffix.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => self.suffix.cmp(&other.suffix),
+        }
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let numbers = self
+            .numbers
+            .iter()
+            .map(|number| number.to_string())
+            .collect::<Vec<_>>()
+            .join(".");
+        if self.suffix.is_empty() {
+            write!(f, "{}", numbers)
+        } else {
+            write!(f, "{}-{}", numbers, self.suffix)
+        }
+    }
+}
+
+/// VersionChange describes the effect of pinning a distribution where another
+/// version of the package is currently in effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VersionChange {
+    /// The new version is newer than the current one
+    Upgrade,
+    /// The new version is older than the current one
+    Downgrade,
+    /// The new version is the current one
+    NoOp,
+}
+
+/// Compare a distribution against the distribution currently in effect
+///
+/// # Arguments
+/// * `distribution` - The distribution being pinned
+/// * `current` - The distribution currently in effect
+///
+/// # Returns
+/// * Some VersionChange if both distributions are versions of the same package
+/// * None if the packages differ, or either version cannot be parsed
+pub fn version_change(distribution: &str, current: &str) -> Option<VersionChange> {
+    if package_name(distribution) != package_name(current) {
+        return None;
+    }
+    let version = Version::from_distribution(distribution)?;
+    let current = Version::from_distribution(current)?;
+    Some(match version.cmp(&current) {
+        Ordering::Greater => VersionChange::Upgrade,
+        Ordering::Less => VersionChange::Downgrade,
+        Ordering::Equal => VersionChange::NoOp,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: &str) -> Version {
+        Version::parse(version).expect("unable to parse version")
+    }
+
+    #[test]
+    fn splits_the_package_from_the_version() {
+        assert_eq!(
+            split_distribution("modelpublish-1.2.0"),
+            ("modelpublish", Some("1.2.0"))
+        );
+        assert_eq!(
+            split_distribution("modelpublish-plugin-1.2.0-rc1"),
+            ("modelpublish-plugin", Some("1.2.0-rc1"))
+        );
+        assert_eq!(split_distribution("modelpublish"), ("modelpublish", None));
+        assert_eq!(split_distribution("-1.2.0"), ("-1.2.0", None));
+        assert_eq!(
+            package_name("modelpublish-plugin-1.2.0"),
+            "modelpublish-plugin"
+        );
+    }
+
+    #[test]
+    fn orders_versions_numerically() {
+        assert!(version("1.10") > version("1.9"));
+        assert!(version("1.10.0") > version("1.9.12"));
+        assert!(version("2.0.0") > version("1.99.99"));
+        assert!(version("1.2.10") > version("1.2.9"));
+        assert_eq!(version("1.2"), version("1.2.0"));
+    }
+
+    #[test]
+    fn orders_pre_releases_below_the_release() {
+        assert!(version("1.2.0-rc1") < version("1.2.0"));
+        assert!(version("1.2.0-beta") < version("1.2.0-rc1"));
+        assert!(version("1.2.0-rc1") > version("1.1.9"));
+        assert_eq!(version("1.2.0_rc1").to_string(), "1.2.0-rc1");
+    }
+
+    #[test]
+    fn rejects_versions_without_a_number() {
+        assert!(Version::parse("rc1").is_none());
+        assert!(Version::parse("").is_none());
+        assert!(Version::from_distribution("modelpublish").is_none());
+    }
+
+    #[test]
+    fn describes_the_change_of_version() {
+        assert_eq!(
+            version_change("modelpublish-1.10.0", "modelpublish-1.9.0"),
+            Some(VersionChange::Upgrade)
+        );
+        assert_eq!(
+            version_change("modelpublish-1.2.0-rc1", "modelpublish-1.2.0"),
+            Some(VersionChange::Downgrade)
+        );
+        assert_eq!(
+            version_change("modelpublish-1.2", "modelpublish-1.2.0"),
+            Some(VersionChange::NoOp)
+        );
+        assert_eq!(
+            version_change("modelpublish-1.2.0", "texturepublish-1.0.0"),
+            None
+        );
+        assert_eq!(version_change("modelpublish-1.2.0", "modelpublish"), None);
+    }
+}
@@ -0,0 +1,537 @@
+//! A client for a packybara-style pin service, exposed over REST. It implements
+//! DataProvider, PinProvider and PinSink, so the dialog may fetch its choices, the
+//! existing pins, and write new pins through the service.
+//!
+//! The service is expected to offer the following endpoints, relative to the base url:
+//!
+//! * `GET roles` - a JSON list of roles
+//! * `GET sites` - a JSON list of sites
+//! * `GET platforms` - a JSON list of platforms
+//! * `GET levels?show=SHOW` - a JSON object mapping each sequence to a list of shots
+//! * `GET distributions?package=PACKAGE` - a JSON list of distributions
+//! * `GET pins?show=SHOW` - a JSON list of pins
+//! * `POST pins` - write the JSON list of pins in the body, all or none of them
+use crate::data_provider::DataProvider;
+use crate::i18n::trf;
+use crate::pin_provider::PinProvider;
+use crate::pin_sink::{PinSink, SinkError};
+use crate::pin_spec::PinSpec;
+use crate::vpin_selection::LevelMap;
+use serde::de::DeserializeOwned;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+/// The default time allowed for a request to complete
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// HttpConfig describes how to reach the service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpConfig {
+    /// The url against which the endpoints are resolved (eg `http://pins.example.com/api`)
+    pub base_url: String,
+    /// The time allowed for each request to complete
+    pub timeout: Duration,
+    /// Some header (name, value) sent with every request to authenticate it
+    pub auth_header: Option<(String, String)>,
+}
+
+impl HttpConfig {
+    /// Create an HttpConfig for the service at the supplied url, with the default
+    /// timeout and no authentication
+    ///
+    /// # Arguments
+    /// * `base_url` - The url against which the endpoints are resolved
+    ///
+    /// # Returns
+    /// * HttpConfig instance
+    pub fn new<I: Into<String>>(base_url: I) -> Self {
+        HttpConfig {
+            base_url: base_url.into(),
+            timeout: DEFAULT_TIMEOUT,
+            auth_header: None,
+        }
+    }
+
+    /// Authenticate each request with a bearer token
+    ///
+    /// # Arguments
+    /// * `token` - The token
+    ///
+    /// # Returns
+    /// * The HttpConfig
+    pub fn with_bearer_token(mut self, token: &str) -> Self {
+        self.auth_header = Some(("Authorization".to_string(), format!("Bearer {}", token)));
+        self
+    }
+}
+
+/// HttpError describes why a request to the service failed
+#[derive(Debug)]
+pub enum HttpError {
+    /// The request did not complete within the timeout
+    Timeout { url: String },
+    /// The service could not be reached
+    Transport { url: String, message: String },
+    /// The service responded with an error status
+    Status {
+        url: String,
+        status: u16,
+        body: String,
+    },
+    /// The response could not be decoded
+    Decode { url: String, message: String },
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            HttpError::Timeout { url } => trf("Request to {} timed out", &[url]),
+            HttpError::Transport { url, message } => trf("Unable to reach {}: {}", &[url, message]),
+            HttpError::Status { url, status, body } => trf(
+                "Request to {} failed with status {}: {}",
+                &[url, status, body],
+            ),
+            HttpError::Decode { url, message } => {
+                trf("Unable to decode the response from {}: {}", &[url, message])
+            }
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+impl Error for HttpError {}
+
+/// HttpClient talks to the pin service
+pub struct HttpClient {
+    config: HttpConfig,
+    agent: ureq::Agent,
+    pending: Option<Vec<PinSpec>>,
+}
+
+impl HttpClient {
+    /// Create a client for the service described by the config
+    ///
+    /// # Arguments
+    /// * `config` - The HttpConfig
+    ///
+    /// # Returns
+    /// * HttpClient instance
+    pub fn new(config: HttpConfig) -> Self {
+        HttpClient {
+            config,
+            agent: ureq::agent(),
+            pending: None,
+        }
+    }
+
+    /// Retrieve the config
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * The HttpConfig
+    pub fn config(&self) -> &HttpConfig {
+        &self.config
+    }
+
+    /// Write the pins to the service in a single request
+    ///
+    /// # Arguments
+    /// * `pins` - The pins
+    ///
+    /// # Returns
+    /// * Ok if the service accepted the pins
+    /// * HttpError otherwise
+    pub fn post_pins(&self, pins: &[PinSpec]) -> Result<(), HttpError> {
+        let url = self.url("pins");
+        let body = serde_json::to_string(pins).map_err(|err| HttpError::Decode {
+            url: url.clone(),
+            message: err.to_string(),
+        })?;
+        let response = self
+            .request("POST", url.as_str(), &[])
+            .set("Content-Type", "application/json")
+            .send_string(body.as_str());
+        Self::check(url.as_str(), response)?;
+        Ok(())
+    }
+
+    // issue a GET request for the endpoint and decode the JSON body
+    fn get<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        query: &[(&str, &str)],
+    ) -> Result<T, HttpError> {
+        let url = self.url(endpoint);
+        let response = self.request("GET", url.as_str(), query).call();
+        let body = Self::check(url.as_str(), response)?
+            .into_string()
+            .map_err(|err| Self::io_error(url.as_str(), err))?;
+        serde_json::from_str(body.as_str()).map_err(|err| HttpError::Decode {
+            url,
+            message: err.to_string(),
+        })
+    }
+
+    // build a request bearing the timeout and auth header
+    fn request(&self, method: &str, url: &str, query: &[(&str, &str)]) -> ureq::Request {
+        let mut request = self.agent.request(method, url);
+        request.timeout(self.config.timeout);
+        if let Some((name, value)) = &self.config.auth_header {
+            request.set(name.as_str(), value.as_str());
+        }
+        for (param, value) in query {
+            request.query(param, value);
+        }
+        request
+    }
+
+    // resolve the endpoint against the base url
+    fn url(&self, endpoint: &str) -> String {
+        format!(
+            "{}/{}",
+            self.config.base_url.trim_end_matches('/'),
+            endpoint
+        )
+    }
+
+    // turn a failed response into an HttpError
+    fn check(url: &str, response: ureq::Response) -> Result<ureq::Response, HttpError> {
+        if response.synthetic() {
+            return Err(match response.into_synthetic_error() {
+                Some(ureq::Error::Io(err)) => Self::io_error(url, err),
+                Some(err) => HttpError::Transport {
+                    url: url.to_string(),
+                    message: err.to_string(),
+                },
+                None => HttpError::Transport {
+                    url: url.to_string(),
+                    message: String::new(),
+                },
+            });
+        }
+        if response.error() {
+            let status = response.status();
+            return Err(HttpError::Status {
+                url: url.to_string(),
+                status,
+                body: response.into_string().unwrap_or_default(),
+            });
+        }
+        Ok(response)
+    }
+
+    // tell a timeout from any other io error
+    fn io_error(url: &str, err: io::Error) -> HttpError {
+        match err.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => HttpError::Timeout {
+                url: url.to_string(),
+            },
+            _ => HttpError::Transport {
+                url: url.to_string(),
+                message: err.to_string(),
+            },
+        }
+    }
+}
+
+impl DataProvider for HttpClient {
+    fn roles(&mut self) -> Result<Vec<String>, SinkError> {
+        Ok(self.get("roles", &[])?)
+    }
+
+    fn levels(&mut self, show: &str) -> Result<LevelMap, SinkError> {
+        Ok(self.get("levels", &[("show", show)])?)
+    }
+
+    fn sites(&mut self) -> Result<Vec<String>, SinkError> {
+        Ok(self.get("sites", &[])?)
+    }
+
+    fn platforms(&mut self) -> Result<Vec<String>, SinkError> {
+        Ok(self.get("platforms", &[])?)
+    }
+
+    fn distributions(&mut self, package: &str) -> Result<Vec<String>, SinkError> {
+        Ok(self.get("distributions", &[("package", package)])?)
+    }
+}
+
+impl PinProvider for HttpClient {
+    fn existing_pins(&mut self, show: &str) -> Result<Vec<PinSpec>, SinkError> {
+        Ok(self.get("pins", &[("show", show)])?)
+    }
+}
+
+// The pins are buffered until the transaction is committed, and then written in a
+// single request, which the service applies atomically
+impl PinSink for HttpClient {
+    fn begin(&mut self) -> Result<(), SinkError> {
+        if self.pending.is_some() {
+            return Err("transaction already in progress".into());
+        }
+        self.pending = Some(Vec::new());
+        Ok(())
+    }
+
+    fn write(&mut self, pin: &PinSpec) -> Result<(), SinkError> {
+        self.pending
+            .as_mut()
+            .ok_or("no transaction in progress")?
+            .push(pin.clone());
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), SinkError> {
+        let pending = self.pending.as_ref().ok_or("no transaction in progress")?;
+        self.post_pins(pending)?;
+        self.pending = None;
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<(), SinkError> {
+        self.pending.take().ok_or("no transaction in progress")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pin_sink::{write_pins, CommitError};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use tiny_http::{Header, Request, Response, Server};
+
+    const TOKEN: &str = "secret";
+
+    // MockService serves the endpoints of the pin service on a local port, recording
+    // the pins posted to it and the Authorization header of each request
+    struct MockService {
+        base_url: String,
+        pins: Arc<Mutex<Vec<PinSpec>>>,
+        auth_headers: Arc<Mutex<Vec<Option<String>>>>,
+    }
+
+    impl MockService {
+        fn start() -> Self {
+            let server = Server::http("127.0.0.1:0").expect("unable to start the mock service");
+            let service = MockService {
+                base_url: format!("http://{}", server.server_addr()),
+                pins: Arc::new(Mutex::new(vec![pin("modelpublish-1.1.0", "DEV01")])),
+                auth_headers: Arc::new(Mutex::new(Vec::new())),
+            };
+            let pins = service.pins.clone();
+            let auth_headers = service.auth_headers.clone();
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    Self::respond(request, &pins, &auth_headers);
+                }
+            });
+            service
+        }
+
+        // a client authenticated with the token, for the endpoints beneath the path
+        fn client(&self, path: &str) -> HttpClient {
+            HttpClient::new(
+                HttpConfig::new(format!("{}{}", self.base_url, path)).with_bearer_token(TOKEN),
+            )
+        }
+
+        fn respond(
+            mut request: Request,
+            pins: &Mutex<Vec<PinSpec>>,
+            auth_headers: &Mutex<Vec<Option<String>>>,
+        ) {
+            let auth_header = request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv("Authorization"))
+                .map(|header| header.value.to_string());
+            let authorized = auth_header == Some(format!("Bearer {}", TOKEN));
+            auth_headers.lock().unwrap().push(auth_header);
+            let url = request.url().to_string();
+            let (status, body) = if url.starts_with("/slow/") {
+                thread::sleep(Duration::from_millis(500));
+                (200, "[]".to_string())
+            } else if url.starts_with("/garbled/") {
+                (200, "{".to_string())
+            } else if !authorized {
+                (401, "unauthorized".to_string())
+            } else if url == "/roles" {
+                (200, r#"["anim", "model", "fx"]"#.to_string())
+            } else if url == "/sites" {
+                (200, r#"["montreal", "vancouver"]"#.to_string())
+            } else if url == "/platforms" {
+                (200, r#"["linux-el7", "linux-el9"]"#.to_string())
+            } else if url == "/levels?show=DEV01" {
+                (200, r#"{"RD": ["0001", "0002"]}"#.to_string())
+            } else if url == "/distributions?package=modelpublish" {
+                (
+                    200,
+                    r#"["modelpublish-1.1.0", "modelpublish-1.2.0"]"#.to_string(),
+                )
+            } else if url == "/pins?show=DEV01" {
+                (200, serde_json::to_string(&*pins.lock().unwrap()).unwrap())
+            } else if url == "/pins" {
+                // the service rejects the whole request should any pin be for
+                // another show
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                match serde_json::from_str::<Vec<PinSpec>>(body.as_str()) {
+                    Ok(new_pins) if new_pins.iter().all(|pin| pin.show() == "DEV01") => {
+                        pins.lock().unwrap().extend(new_pins);
+                        (201, String::new())
+                    }
+                    _ => (422, "unknown show".to_string()),
+                }
+            } else {
+                (404, "not found".to_string())
+            };
+            let response = Response::from_string(body)
+                .with_status_code(status)
+                .with_header("Content-Type: application/json".parse::<Header>().unwrap());
+            request.respond(response).unwrap();
+        }
+    }
+
+    fn pin(distribution: &str, level: &str) -> PinSpec {
+        PinSpec {
+            distribution: distribution.to_string(),
+            level: level.to_string(),
+            role: None,
+            site: None,
+            platform: None,
+            comment: None,
+            ticket: None,
+            effective_from: None,
+            expires_on: None,
+        }
+    }
+
+    // Recover the HttpError from the error returned by a provider
+    fn http_error(err: SinkError) -> HttpError {
+        match err.downcast::<HttpError>() {
+            Ok(err) => *err,
+            Err(err) => panic!("expected an HttpError, got {}", err),
+        }
+    }
+
+    #[test]
+    fn fetches_the_choices_and_existing_pins() {
+        let service = MockService::start();
+        let mut client = service.client("");
+        assert_eq!(client.roles().unwrap(), vec!["anim", "model", "fx"]);
+        assert_eq!(client.sites().unwrap(), vec!["montreal", "vancouver"]);
+        assert_eq!(client.platforms().unwrap(), vec!["linux-el7", "linux-el9"]);
+        assert_eq!(client.levels("DEV01").unwrap()["RD"], vec!["0001", "0002"]);
+        assert_eq!(
+            client.distributions("modelpublish").unwrap(),
+            vec!["modelpublish-1.1.0", "modelpublish-1.2.0"]
+        );
+        assert_eq!(
+            client.existing_pins("DEV01").unwrap(),
+            vec![pin("modelpublish-1.1.0", "DEV01")]
+        );
+    }
+
+    #[test]
+    fn sends_the_bearer_token_with_every_request() {
+        let service = MockService::start();
+        let mut client = service.client("");
+        client.roles().unwrap();
+        client.existing_pins("DEV01").unwrap();
+        write_pins(&mut client, &[pin("modelpublish-1.2.0", "DEV01")]).unwrap();
+        let expected = Some(format!("Bearer {}", TOKEN));
+        assert_eq!(*service.auth_headers.lock().unwrap(), vec![expected; 3]);
+    }
+
+    #[test]
+    fn reports_an_unauthorized_request() {
+        let service = MockService::start();
+        let mut anonymous = HttpClient::new(HttpConfig::new(service.base_url.as_str()));
+        match http_error(anonymous.roles().unwrap_err()) {
+            HttpError::Status {
+                status: 401, body, ..
+            } => assert_eq!(body, "unauthorized"),
+            err => panic!("expected a 401, got {}", err),
+        }
+        assert_eq!(*service.auth_headers.lock().unwrap(), vec![None]);
+    }
+
+    #[test]
+    fn reports_a_response_which_cannot_be_decoded() {
+        let service = MockService::start();
+        match http_error(
+            service
+                .client("/garbled")
+                .existing_pins("DEV01")
+                .unwrap_err(),
+        ) {
+            HttpError::Decode { url, .. } => {
+                assert_eq!(url, format!("{}/garbled/pins", service.base_url))
+            }
+            err => panic!("expected a decoding error, got {}", err),
+        }
+    }
+
+    #[test]
+    fn reports_a_request_which_times_out() {
+        let service = MockService::start();
+        let mut slow = HttpClient::new(HttpConfig {
+            timeout: Duration::from_millis(100),
+            ..service.client("/slow").config().clone()
+        });
+        match http_error(slow.roles().unwrap_err()) {
+            HttpError::Timeout { .. } => (),
+            err => panic!("expected a timeout, got {}", err),
+        }
+    }
+
+    #[test]
+    fn reports_a_service_which_cannot_be_reached() {
+        let mut unreachable = HttpClient::new(HttpConfig::new("http://127.0.0.1:1"));
+        match http_error(unreachable.sites().unwrap_err()) {
+            HttpError::Transport { .. } => (),
+            err => panic!("expected a transport error, got {}", err),
+        }
+    }
+
+    #[test]
+    fn writes_the_pins_together_or_not_at_all() {
+        let service = MockService::start();
+        let mut client = service.client("");
+        write_pins(
+            &mut client,
+            &[
+                pin("modelpublish-1.2.0", "DEV01.RD"),
+                pin("modelpublish-1.2.0", "DEV01.AA"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(service.pins.lock().unwrap().len(), 3);
+        match write_pins(
+            &mut client,
+            &[
+                pin("modelpublish-1.2.0", "DEV01"),
+                pin("modelpublish-1.2.0", "DEV02"),
+            ],
+        ) {
+            Err(CommitError::Commit {
+                error,
+                rollback: None,
+            }) => match error.downcast_ref::<HttpError>() {
+                Some(HttpError::Status { status: 422, .. }) => (),
+                _ => panic!("expected a 422, got {}", error),
+            },
+            other => panic!("expected the commit to fail, got {:?}", other),
+        }
+        assert_eq!(service.pins.lock().unwrap().len(), 3);
+        // the failed transaction was rolled back, so another may begin
+        write_pins(&mut client, &[pin("modelpublish-1.3.0", "DEV01")]).unwrap();
+        assert_eq!(service.pins.lock().unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,350 @@
+//! Translation of user facing strings. Strings are looked up by their English text
+//! in a Catalog, falling back to the English text when no translation exists.
+//! Catalogs are TOML files mapping the English text to its translation, and a
+//! French catalog is bundled.
+//!
+//! The catalog is chosen from the environment the first time a string is translated:
+//! `PBGUI_VPIN_CATALOG` names a catalog file, otherwise the locale is read from
+//! `PBGUI_VPIN_LOCALE`, `LC_ALL`, `LC_MESSAGES` or `LANG`. A catalog file which cannot
+//! be read is passed over in favour of the locale; `Catalog::from_env` reports why.
+//! Either may be overridden with `set_locale` or `set_catalog`, which must be called
+//! before the dialog is created.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Environment variable naming the locale, which takes precedence over `LANG` et al
+pub const LOCALE_ENV_VAR: &str = "PBGUI_VPIN_LOCALE";
+/// Environment variable naming a catalog file, which takes precedence over the locale
+pub const CATALOG_ENV_VAR: &str = "PBGUI_VPIN_CATALOG";
+
+const FRENCH_CATALOG: &str = include_str!("../resources/i18n/fr.toml");
+
+/// Locale identifies the languages with a bundled catalog
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    English,
+    French,
+}
+
+impl Locale {
+    /// Determine the locale from a POSIX locale code such as `fr_CA.UTF-8`.
+    /// Unsupported languages fall back to English.
+    ///
+    /// # Arguments
+    /// * `code` - The locale code
+    ///
+    /// # Returns
+    /// * Locale
+    pub fn from_code(code: &str) -> Self {
+        let language = code.split(['_', '-', '.', '@']).next().unwrap_or("");
+        match language.to_lowercase().as_str() {
+            "fr" => Locale::French,
+            _ => Locale::English,
+        }
+    }
+
+    /// Determine the locale from the environment, consulting `PBGUI_VPIN_LOCALE`,
+    /// `LC_ALL`, `LC_MESSAGES` and `LANG` in turn
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Locale
+    pub fn from_env() -> Self {
+        Locale::from_vars(|var| std::env::var(var).ok())
+    }
+
+    // Determine the locale from the first of the locale variables which is set,
+    // looking each up via the supplied function
+    fn from_vars<F: Fn(&str) -> Option<String>>(lookup: F) -> Self {
+        [LOCALE_ENV_VAR, "LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| lookup(var))
+            .find(|code| !code.is_empty())
+            .map(|code| Locale::from_code(code.as_str()))
+            .unwrap_or_default()
+    }
+}
+
+/// Catalog maps English strings to their translation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Catalog {
+    translations: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Create an empty Catalog, which leaves every string in English
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Catalog instance
+    pub fn new() -> Self {
+        Catalog::default()
+    }
+
+    /// Retrieve the bundled Catalog for the supplied locale
+    ///
+    /// # Arguments
+    /// * `locale` - The Locale
+    ///
+    /// # Returns
+    /// * Catalog instance
+    pub fn for_locale(locale: Locale) -> Self {
+        match locale {
+            Locale::English => Catalog::new(),
+            Locale::French => {
+                Catalog::from_toml_str(FRENCH_CATALOG).expect("bundled French catalog is invalid")
+            }
+        }
+    }
+
+    /// Parse a Catalog from a TOML string whose keys are the English strings and
+    /// whose values are the translations
+    ///
+    /// # Arguments
+    /// * `toml_str` - The catalog contents
+    ///
+    /// # Returns
+    /// * Catalog if the contents are valid
+    /// * toml::de::Error otherwise
+    pub fn from_toml_str(toml_str: &str) -> Result<Self, toml::de::Error> {
+        Ok(Catalog {
+            translations: toml::from_str(toml_str)?,
+        })
+    }
+
+    /// Read a Catalog from a TOML file
+    ///
+    /// # Arguments
+    /// * `path` - The path to the catalog file
+    ///
+    /// # Returns
+    /// * Catalog if the file could be read and parsed
+    /// * io::Error otherwise
+    pub fn from_path(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        Catalog::from_toml_str(contents.as_str())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Add or replace a translation
+    ///
+    /// # Arguments
+    /// * `text` - The English text
+    /// * `translation` - The translated text
+    ///
+    /// # Returns
+    /// * None
+    pub fn insert<I: Into<String>, J: Into<String>>(&mut self, text: I, translation: J) {
+        self.translations.insert(text.into(), translation.into());
+    }
+
+    /// Translate the supplied text, falling back to the text itself
+    ///
+    /// # Arguments
+    /// * `text` - The English text
+    ///
+    /// # Returns
+    /// * The translation as a &str
+    pub fn translate<'a>(&'a self, text: &'a str) -> &'a str {
+        self.translations
+            .get(text)
+            .map(String::as_str)
+            .unwrap_or(text)
+    }
+
+    /// Build the Catalog from the environment: the catalog file named by
+    /// `PBGUI_VPIN_CATALOG` if set, or the bundled catalog for the locale otherwise
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Catalog instance
+    /// * io::Error, naming the file, if `PBGUI_VPIN_CATALOG` is set but the file
+    ///   could not be read or parsed
+    pub fn from_env() -> io::Result<Self> {
+        match std::env::var_os(CATALOG_ENV_VAR).filter(|path| !path.is_empty()) {
+            Some(path) => {
+                let path = Path::new(&path);
+                Catalog::from_path(path).map_err(|err| {
+                    io::Error::new(
+                        err.kind(),
+                        format!("unable to load catalog {}: {}", path.display(), err),
+                    )
+                })
+            }
+            None => Ok(Catalog::for_locale(Locale::from_env())),
+        }
+    }
+}
+
+thread_local! {
+    // a catalog file which cannot be read falls back to the locale. Callers wishing
+    // to report the failure should check Catalog::from_env themselves
+    static CATALOG: RefCell<Catalog> = RefCell::new(
+        Catalog::from_env().unwrap_or_else(|_| Catalog::for_locale(Locale::from_env())),
+    );
+}
+
+/// Install the bundled catalog for the supplied locale. This must be called before
+/// the dialog is created.
+///
+/// # Arguments
+/// * `locale` - The Locale
+///
+/// # Returns
+/// * None
+pub fn set_locale(locale: Locale) {
+    set_catalog(Catalog::for_locale(locale));
+}
+
+/// Install the supplied catalog. This must be called before the dialog is created.
+///
+/// # Arguments
+/// * `catalog` - The Catalog
+///
+/// # Returns
+/// * None
+pub fn set_catalog(catalog: Catalog) {
+    CATALOG.with(|current| current.replace(catalog));
+}
+
+/// Translate the supplied text using the current catalog
+///
+/// # Arguments
+/// * `text` - The English text
+///
+/// # Returns
+/// * The translation as a String
+pub fn tr(text: &str) -> String {
+    CATALOG.with(|catalog| catalog.borrow().translate(text).to_string())
+}
+
+/// Translate the supplied text using the current catalog, and substitute each `{}`
+/// placeholder with the corresponding argument
+///
+/// # Arguments
+/// * `text` - The English text, containing a `{}` for each argument
+/// * `args` - The arguments
+///
+/// # Returns
+/// * The translation as a String
+pub fn trf(text: &str, args: &[&dyn Display]) -> String {
+    let translated = tr(text);
+    let mut pieces = translated.split("{}");
+    let mut result = pieces.next().unwrap_or("").to_string();
+    let mut args = args.iter();
+    for piece in pieces {
+        if let Some(arg) = args.next() {
+            result.push_str(arg.to_string().as_str());
+        }
+        result.push_str(piece);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_language_from_the_locale_code() {
+        assert_eq!(Locale::from_code("fr"), Locale::French);
+        assert_eq!(Locale::from_code("fr_CA.UTF-8"), Locale::French);
+        assert_eq!(Locale::from_code("FR-fr"), Locale::French);
+        assert_eq!(Locale::from_code("fr@euro"), Locale::French);
+        assert_eq!(Locale::from_code("en_US.UTF-8"), Locale::English);
+        assert_eq!(Locale::from_code("de_DE"), Locale::English);
+        assert_eq!(Locale::from_code(""), Locale::English);
+    }
+
+    #[test]
+    fn consults_the_locale_variables_in_order_of_precedence() {
+        let vars = |set: &'static [(&'static str, &'static str)]| {
+            move |var: &str| {
+                set.iter()
+                    .find(|(name, _)| *name == var)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert_eq!(
+            Locale::from_vars(vars(&[
+                (LOCALE_ENV_VAR, "fr_FR"),
+                ("LC_ALL", "en_US"),
+                ("LC_MESSAGES", "en_US"),
+                ("LANG", "en_US"),
+            ])),
+            Locale::French
+        );
+        assert_eq!(
+            Locale::from_vars(vars(&[
+                ("LC_ALL", "en_US"),
+                ("LC_MESSAGES", "fr_FR"),
+                ("LANG", "fr_FR"),
+            ])),
+            Locale::English
+        );
+        assert_eq!(
+            Locale::from_vars(vars(&[("LC_MESSAGES", "fr_FR"), ("LANG", "en_US")])),
+            Locale::French
+        );
+        assert_eq!(
+            Locale::from_vars(vars(&[("LANG", "fr_FR.UTF-8")])),
+            Locale::French
+        );
+        // empty variables are passed over
+        assert_eq!(
+            Locale::from_vars(vars(&[(LOCALE_ENV_VAR, ""), ("LANG", "fr_FR")])),
+            Locale::French
+        );
+        assert_eq!(Locale::from_vars(vars(&[])), Locale::English);
+    }
+
+    #[test]
+    fn falls_back_to_the_english_text() {
+        let mut catalog = Catalog::new();
+        catalog.insert("Cancel", "Annuler");
+        assert_eq!(catalog.translate("Cancel"), "Annuler");
+        assert_eq!(catalog.translate("Apply"), "Apply");
+    }
+
+    #[test]
+    fn bundles_a_valid_french_catalog() {
+        let catalog = Catalog::for_locale(Locale::French);
+        assert_ne!(catalog, Catalog::new());
+        assert_eq!(Catalog::for_locale(Locale::English), Catalog::new());
+    }
+
+    #[test]
+    fn substitutes_placeholders_in_order() {
+        // the catalog is thread local, so this does not affect other tests
+        set_catalog(Catalog::new());
+        assert_eq!(
+            trf("Unknown shot: {} in sequence {}", &[&"0001", &"RD"]),
+            "Unknown shot: 0001 in sequence RD"
+        );
+        assert_eq!(trf("{} of {} selected", &[&2, &5]), "2 of 5 selected");
+        assert_eq!(trf("No placeholders", &[&1]), "No placeholders");
+        // missing arguments leave the placeholder empty
+        assert_eq!(trf("{} and {}", &[&"one"]), "one and ");
+    }
+
+    #[test]
+    fn substitutes_placeholders_in_the_translation() {
+        let mut catalog = Catalog::new();
+        catalog.insert("Unknown role: {}", "Rôle inconnu : {}");
+        set_catalog(catalog);
+        assert_eq!(trf("Unknown role: {}", &[&"fx"]), "Rôle inconnu : fx");
+        assert_eq!(tr("Unknown site: {}"), "Unknown site: {}");
+        set_locale(Locale::English);
+    }
+}
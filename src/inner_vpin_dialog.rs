@@ -1,40 +1,68 @@
 #![allow(dead_code)]
 //! The DistributionDialog allows the user to generate one or more pins for a distribution
-use qt_core::{FocusPolicy, QString, Signal, SlotOfInt};
+use crate::accessibility::{set_accessible, set_tab_chain, unlabelled_widgets};
+use crate::conflict::{detect_conflicts, Conflict, ConflictKind};
+use crate::distribution::{package_name, VersionChange};
+use crate::i18n::{tr, trf};
+use crate::loader::Section;
+use crate::pin_spec::PinSpec;
+use crate::resolver::{resolve, version_changes, PinChange};
+use crate::stylesheet::{
+    apply_stylesheet_from_path, stylesheet_path_from_env, watch_stylesheet_from_env,
+    StylesheetWatcher,
+};
+use crate::theme::{Theme, STYLE_STR};
+use crate::validation::{ticket_regex, warnings, SelectionError};
+use crate::vpin_selection::VpinSelection;
+use crate::vpin_selector_widget::VpinSelectorWidget;
+use qt_core::{QDate, Signal, Slot};
 use qt_widgets::{
     cpp_core::{CastInto, CppBox, MutPtr, Ptr},
     q_abstract_item_view::SelectionMode,
     q_dialog_button_box::StandardButton,
-    QCheckBox, QComboBox, QDialog, QDialogButtonBox, QFrame, QGroupBox, QHBoxLayout, QLabel,
-    QLayout, QLineEdit, QListWidget, QVBoxLayout, QWidget,
+    QCheckBox, QDateEdit, QDialog, QDialogButtonBox, QFrame, QHBoxLayout, QLabel, QLineEdit,
+    QListWidget, QListWidgetItem, QPlainTextEdit, QProgressBar, QPushButton, QVBoxLayout, QWidget,
 };
-use std::cell::RefCell;
-use std::collections::HashMap;
-
-/// LevelMap maps a sequence to a list of shots
-pub type LevelMap = HashMap<String, Vec<String>>;
 
+pub use crate::vpin_selector_widget::LevelMap;
 pub use rustqt_utils::{create_hlayout, create_vlayout, qs, set_stylesheet_from_str};
+use std::cell::{Cell, RefCell};
+use std::io;
+use std::path::Path;
+
+// The format of the dates exchanged with the date pickers, as understood by QDate
+const DATE_FORMAT: &str = "yyyy-MM-dd";
+// How many days from today the expiry date picker starts at
+const DEFAULT_EXPIRY_DAYS: i64 = 30;
 
-const STYLE_STR: &'static str = include_str!("../resources/pbgui_vpin.qss");
-const DEFAULT_SEQ: &'static str = "All Sequences";
-const DEFAULT_SHOT: &'static str = "All Shots";
-pub const DEFAULT_SITE: &'static str = "any";
 pub struct InnerVpinDialog<'a> {
     dialog: CppBox<QDialog>,
-    show: RefCell<String>,
-    distribution: MutPtr<QLabel>,
-    roles_checkbox: MutPtr<QCheckBox>,
-    roles_filter: MutPtr<QLineEdit>,
-    roles_list: MutPtr<QListWidget>,
-    seqs_cbox: MutPtr<QComboBox>,
-    seq_shot_checkbox: MutPtr<QCheckBox>,
-    shots_cbox: MutPtr<QComboBox>,
-    sites_cbox: MutPtr<QComboBox>,
+    selector: VpinSelectorWidget<'a>,
+    comment_label: MutPtr<QLabel>,
+    comment_edit: MutPtr<QPlainTextEdit>,
+    comment_required: Cell<bool>,
+    ticket_label: MutPtr<QLabel>,
+    ticket_edit: MutPtr<QLineEdit>,
+    ticket_required: Cell<bool>,
+    ticket_pattern: RefCell<Option<String>>,
+    effective_checkbox: MutPtr<QCheckBox>,
+    effective_edit: MutPtr<QDateEdit>,
+    expiry_checkbox: MutPtr<QCheckBox>,
+    expiry_edit: MutPtr<QDateEdit>,
+    preview: MutPtr<QListWidget>,
+    pins_loading: MutPtr<QProgressBar>,
+    loading_pins: Cell<bool>,
+    existing_pins: Vec<PinSpec>,
+    conflicts_confirmed: Cell<bool>,
+    pin_errors: RefCell<Vec<(PinSpec, String)>>,
+    downgrade_checkbox: MutPtr<QCheckBox>,
+    warning_label: MutPtr<QLabel>,
+    error_label: MutPtr<QLabel>,
     buttons: MutPtr<QDialogButtonBox>,
-    levels: LevelMap,
-    seq_shot_cb_slot: SlotOfInt<'a>,
-    roles_cb_slot: SlotOfInt<'a>,
+    apply_button: MutPtr<QPushButton>,
+    refresh_button: MutPtr<QPushButton>,
+    shortcuts_help: MutPtr<QLabel>,
+    stylesheet_watcher: Option<StylesheetWatcher<'a>>,
 }
 
 impl<'a> InnerVpinDialog<'a> {
@@ -47,155 +75,194 @@ impl<'a> InnerVpinDialog<'a> {
         unsafe {
             let mut dialog = QDialog::new_1a(parent);
             dialog.set_object_name(&qs("AddVersionPinDialog"));
-            dialog.set_window_title(&qs("Add Version-Pin"));
+            dialog.set_window_title(&qs(tr("Add Version-Pin").as_str()));
             let mut layout = create_vlayout();
-            let mut layout_ptr = layout.as_mut_ptr();
-
-            Self::add_entry_label(layout_ptr);
-
-            let distribution_label = Self::add_distribution_label(distribution, layout_ptr);
-
-            // hlayout will contain the two column  vertical layouts (left and right)
-            let mut hlayout = create_hlayout();
-            let hlayout_ptr = hlayout.as_mut_ptr();
-            layout_ptr.add_layout_1a(hlayout.into_ptr());
-
-            // Left side controls
-            let left_layout = Self::add_left_layout(hlayout_ptr);
-            let roles_checkbox = Self::add_roles_checkbox(left_layout);
-            let mut group_box = Self::add_select_roles_groupbox(left_layout);
-            let roles_filter = Self::add_roles_filter(group_box.layout());
-            let roles_list = Self::add_roles_listwidget(group_box.layout());
-            let _roles_list_cpy = roles_list.as_ptr();
-
-            // right side controls
-            let mut right_layout = Self::add_right_layout(hlayout_ptr);
-            //
-            let seq_shot_checkbox = Self::add_seq_shot_checkbox(right_layout);
-            let seq_shot_group_box = Self::add_select_level_groupbox(right_layout);
-            let seqs_cbox = Self::add_seq_cbox(seq_shot_group_box.layout());
-            let mut seqs_cbox_ref = seqs_cbox
-                .as_mut_ref()
-                .expect("unable to get mut ref to seq combobox from mutptr");
-            let shots_cbox = Self::add_shot_cbox(seq_shot_group_box.layout());
-            let mut shots_cbox_ref = shots_cbox
-                .as_mut_ref()
-                .expect("unable to get mut ref to shot combobox from mutptr");
-
-            let sel_sites_group_box = Self::add_select_site_groupbox(right_layout);
-            let sites_cbox = Self::add_site_cbox(sel_sites_group_box.layout());
-            right_layout.add_stretch_1a(1);
+            let layout_ptr = layout.as_mut_ptr();
+
+            // the selector hosts the role / level / site controls
+            let selector = VpinSelectorWidget::create(show, distribution, dialog.as_mut_ptr());
+            layout.add_widget(selector.widget());
+            let (comment_label, comment_edit) = Self::add_comment_edit(layout_ptr);
+            let (ticket_label, ticket_edit) = Self::add_ticket_edit(layout_ptr);
+            // the dates share a row, and are each unspecified until checked
+            let mut dates_row = create_hlayout();
+            let dates_row_ptr = dates_row.as_mut_ptr();
+            let today = QDate::current_date();
+            let (effective_checkbox, effective_edit) = Self::add_date_edit(
+                dates_row_ptr,
+                tr("Effective from").as_str(),
+                "EffectiveFrom",
+                &today,
+            );
+            let (expiry_checkbox, expiry_edit) = Self::add_date_edit(
+                dates_row_ptr,
+                tr("Expires on").as_str(),
+                "ExpiresOn",
+                &today.add_days(DEFAULT_EXPIRY_DAYS),
+            );
+            dates_row.add_stretch_1a(1);
+            layout.add_layout_1a(dates_row.into_ptr());
+            let preview = Self::add_preview(layout_ptr);
+            let pins_loading = Self::add_pins_loading_indicator(layout_ptr);
+
             layout.add_stretch_1a(1);
             let mut strut = QFrame::new_0a();
             strut.set_fixed_size_2a(100, 50);
             strut.set_object_name(&qs("SelectVpinStrut"));
             layout.add_widget(strut.into_ptr());
+            let downgrade_checkbox = Self::add_downgrade_checkbox(layout_ptr);
+            let warning_label = Self::add_warning_label(layout_ptr);
+            let error_label = Self::add_error_label(layout_ptr);
             // Ok and Cancel buttons via QDialogButtonBox
             let mut button_box = QDialogButtonBox::from_q_flags_standard_button(
                 StandardButton::Ok | StandardButton::Cancel,
             );
             let buttons = button_box.as_mut_ptr();
-            layout.add_widget(button_box.into_ptr());
+            // the Apply button is only offered when the dialog is non-modal
+            let mut apply_button = button_box.add_button_standard_button(StandardButton::Apply);
+            apply_button.set_visible(false);
+            // the standard buttons are labelled by Qt, which only translates them when
+            // the host application installs a QTranslator. So we label them ourselves
+            apply_button.set_text(&qs(tr("Apply").as_str()));
+            button_box
+                .button(StandardButton::Ok)
+                .set_text(&qs(tr("OK").as_str()));
+            button_box
+                .button(StandardButton::Cancel)
+                .set_text(&qs(tr("Cancel").as_str()));
+            // the buttons share a row with the shortcuts help, whose tooltip lists
+            // the keyboard shortcuts
+            let mut button_row = create_hlayout();
+            let mut shortcuts_help = QLabel::from_q_string(&qs(tr("Shortcuts").as_str()));
+            shortcuts_help.set_object_name(&qs("ShortcutsHelpLabel"));
+            let shortcuts_help_ptr = shortcuts_help.as_mut_ptr();
+            button_row.add_widget(shortcuts_help.into_ptr());
+            // the Refresh button is only offered once there is something to refresh
+            let mut refresh_button = QPushButton::from_q_string(&qs(tr("Refresh").as_str()));
+            refresh_button.set_object_name(&qs("RefreshButton"));
+            refresh_button.set_auto_default(false);
+            refresh_button.set_visible(false);
+            let refresh_button_ptr = refresh_button.as_mut_ptr();
+            button_row.add_widget(refresh_button.into_ptr());
+            button_row.add_widget(button_box.into_ptr());
+            layout.add_layout_1a(button_row.into_ptr());
             dialog.set_layout(layout.into_ptr());
             dialog.set_modal(true);
-            // create some references to components so that we can use them
-            // in Slots
-            let mut roles_list_ref = roles_list
-                .as_mut_ref()
-                .expect("unable to get reference to roles list");
-            let mut roles_filter_ref = roles_filter
-                .as_mut_ref()
-                .expect("unable to get ref to roles_filter");
-            // default to disabled
-            seqs_cbox_ref.set_enabled(false);
-            shots_cbox_ref.set_enabled(false);
-
-            roles_list_ref.set_enabled(false);
-            roles_filter_ref.set_enabled(false);
             // create the dialog
-            let mut dialog = InnerVpinDialog {
+            let dialog = InnerVpinDialog {
                 dialog,
-                show: RefCell::new(show.into()),
-                distribution: distribution_label,
-                roles_checkbox,
-                roles_filter: roles_filter,
-                roles_list,
-                seq_shot_checkbox,
-                seqs_cbox,
-                shots_cbox,
-                sites_cbox,
+                selector,
+                comment_label,
+                comment_edit,
+                comment_required: Cell::new(false),
+                ticket_label,
+                ticket_edit,
+                ticket_required: Cell::new(false),
+                ticket_pattern: RefCell::new(None),
+                effective_checkbox,
+                effective_edit,
+                expiry_checkbox,
+                expiry_edit,
+                preview,
+                pins_loading,
+                loading_pins: Cell::new(false),
+                existing_pins: Vec::new(),
+                conflicts_confirmed: Cell::new(false),
+                pin_errors: RefCell::new(Vec::new()),
+                downgrade_checkbox,
+                warning_label,
+                error_label,
                 buttons,
-                levels: LevelMap::new(),
-                seq_shot_cb_slot: SlotOfInt::new(move |active: std::os::raw::c_int| {
-                    if active > 0 {
-                        seqs_cbox_ref.set_enabled(true);
-                        shots_cbox_ref.set_enabled(true);
-                    } else {
-                        seqs_cbox_ref.set_enabled(false);
-                        shots_cbox_ref.set_enabled(false);
-                    }
-                }),
-                roles_cb_slot: SlotOfInt::new(move |active: std::os::raw::c_int| {
-                    if active > 0 {
-                        roles_list_ref.set_enabled(true);
-                        roles_filter_ref.set_enabled(true);
-                        group_box.set_enabled(true);
-                        roles_list_ref.set_focus_0a();
-                    } else {
-                        roles_list_ref.set_enabled(false);
-                        group_box.set_enabled(false);
-                        roles_filter_ref.set_enabled(false);
-                    }
-                }),
+                apply_button,
+                refresh_button: refresh_button_ptr,
+                shortcuts_help: shortcuts_help_ptr,
+                stylesheet_watcher: None,
             };
-            // set up internal signals and slots
-            // Enable / Disable roles list and filter
-            dialog
-                .seq_shot_checkbox
-                .state_changed()
-                .connect(&dialog.seq_shot_cb_slot);
-            dialog
-                .roles_checkbox
-                .state_changed()
-                .connect(&dialog.roles_cb_slot);
+            dialog.setup_accessibility();
             // connect the Cancel button to a slot that dismisses the dialog
             buttons.rejected().connect(dialog.dialog.slot_reject());
-            // set teh roles_lsit focus
-            dialog.roles_list.set_focus_0a();
-            // clear the roles_filter focus
-            dialog.roles_filter.clear_focus();
 
             // return the dialog
             dialog
         }
     }
-    /// retrieve the show_name
-    pub fn show_name(&self) -> String {
-        self.show.borrow().clone()
-    }
-    /// set the show name
-    pub fn set_show_name(&self, new_name: String) {
-        self.show.replace(new_name);
-    }
-    /// retrieve the current distribution as a string
-    pub unsafe fn distribution(&self) -> String {
-        self.distribution.text().to_std_string()
-    }
-    /// Retrieve an owned QString of the distribution text
-    pub unsafe fn distribution_qs(&self) -> CppBox<QString> {
-        self.distribution.text()
-    }
-    /// Set the distribution given a distribution &str
-    pub unsafe fn set_distribution(&self, distribution: &str) {
-        let mut distribution_mut = self.distribution;
-        distribution_mut.set_text(&qs(distribution));
+
+    /// Retrieve a reference to the VpinSelectorWidget hosting the controls
+    pub fn selector(&self) -> &VpinSelectorWidget<'a> {
+        &self.selector
     }
+
     /// Return the accepted signal from the button. This is provided as a convenience
     /// for hooking up a slot from this struct.
     pub unsafe fn accepted(&self) -> Signal<()> {
         self.buttons.accepted()
     }
 
+    /// Return the clicked signal from the Apply button
+    pub unsafe fn applied(&self) -> Signal<(bool,)> {
+        self.apply_button.clicked()
+    }
+
+    /// Return the clicked signal from the Refresh button
+    pub unsafe fn refreshed(&self) -> Signal<(bool,)> {
+        self.refresh_button.clicked()
+    }
+
+    /// Retrieve the Refresh button
+    pub unsafe fn refresh_button(&self) -> MutPtr<QPushButton> {
+        self.refresh_button
+    }
+
+    /// Show or hide the Refresh button
+    pub unsafe fn set_refresh_visible(&self, visible: bool) {
+        let mut refresh_button = self.refresh_button;
+        refresh_button.set_visible(visible);
+    }
+
+    /// Retrieve a mutable pointer to the OK button
+    pub unsafe fn ok_button(&self) -> MutPtr<QPushButton> {
+        self.buttons.button(StandardButton::Ok)
+    }
+
+    /// Retrieve the interactive widgets in the dialog, in tab order: those of the
+    /// selector, followed by the comment, ticket, dates, preview and buttons
+    pub unsafe fn interactive_widgets(&self) -> Vec<MutPtr<QWidget>> {
+        let mut widgets = self.selector.interactive_widgets();
+        widgets.push(self.comment_edit.cast_into());
+        widgets.push(self.ticket_edit.cast_into());
+        widgets.push(self.effective_checkbox.cast_into());
+        widgets.push(self.effective_edit.cast_into());
+        widgets.push(self.expiry_checkbox.cast_into());
+        widgets.push(self.expiry_edit.cast_into());
+        widgets.push(self.preview.cast_into());
+        widgets.push(self.downgrade_checkbox.cast_into());
+        widgets.push(self.ok_button().cast_into());
+        widgets.push(self.buttons.button(StandardButton::Cancel).cast_into());
+        widgets.push(self.apply_button.cast_into());
+        widgets.push(self.refresh_button.cast_into());
+        widgets
+    }
+
+    /// Retrieve the object names of any focusable widgets in the dialog which lack
+    /// an accessible name. This should always be empty.
+    pub unsafe fn unlabelled_widgets(&self) -> Vec<String> {
+        unlabelled_widgets(self.dialog.as_ptr())
+    }
+
+    /// Set the help text listing the keyboard shortcuts, displayed as a tooltip
+    pub unsafe fn set_shortcuts_help(&self, help: &str) {
+        let mut shortcuts_help = self.shortcuts_help;
+        shortcuts_help.set_tool_tip(&qs(help));
+        shortcuts_help.set_accessible_description(&qs(help));
+    }
+
+    /// Set whether the dialog is modal. A non-modal dialog offers an Apply button,
+    /// which reports the current selection without closing the dialog.
+    pub unsafe fn set_modal(&mut self, modal: bool) {
+        self.dialog.set_modal(modal);
+        self.apply_button.set_visible(!modal);
+    }
+
     /// Dismiss the dialog using accept. This is a convenience for consumrs
     /// of this struct, to avoid having to drill down
     pub unsafe fn accept(&mut self) {
@@ -217,160 +284,404 @@ impl<'a> InnerVpinDialog<'a> {
         self.buttons.rejected()
     }
 
-    /// Return a lsit of selected item names
-    pub unsafe fn selected_roles(&self) -> Option<Vec<String>> {
-        if !self.roles_checkbox.is_checked() {
-            return None;
-        };
-        let mut results = Vec::new();
+    /// Gather the current state of the widgets into a VpinSelection
+    pub unsafe fn selection(&self) -> VpinSelection {
+        let mut selection = self.selector.selection();
+        selection.comment = self.comment();
+        selection.ticket = self.ticket();
+        selection.effective_from = self.effective_from();
+        selection.expires_on = self.expires_on();
+        selection
+    }
 
-        if self.roles_list.is_null() {
-            panic!("roles_list pointer is null")
-        };
-        let mut items = self.roles_list.selected_items();
-        if !items.is_empty() {
-            for _c in 0..items.length() {
-                let item = items.take_first();
-                results.push(item.text().to_std_string());
-            }
-        }
-        Some(results)
+    /// Gather the current state of the widgets into a VpinSelection, validated
+    /// against the roles, levels and sites on offer, and the comment and ticket rules
+    pub unsafe fn validated_selection(&self) -> Result<VpinSelection, SelectionError> {
+        let selection = self.selection();
+        let mut context = self.selector.context();
+        context.comment_required = self.comment_required.get();
+        context.ticket_required = self.ticket_required.get();
+        context.ticket_pattern = self.ticket_pattern.borrow().clone();
+        context.validate(&selection)?;
+        Ok(selection)
     }
 
-    /// Retrieve the current site
-    pub unsafe fn selected_site(&self) -> String {
-        self.sites_cbox.current_text().to_std_string()
+    /// Retrieve the comment, if any. Surrounding whitespace is trimmed.
+    pub unsafe fn comment(&self) -> Option<String> {
+        let comment = self.comment_edit.to_plain_text().to_std_string();
+        Some(comment.trim().to_string()).filter(|comment| !comment.is_empty())
     }
 
-    /// Load the stylesheet
-    pub unsafe fn set_default_stylesheet(&mut self) {
-        set_stylesheet_from_str(STYLE_STR, self.dialog.as_mut_ptr());
+    /// Set the comment
+    pub unsafe fn set_comment(&self, comment: &str) {
+        let mut comment_edit = self.comment_edit;
+        comment_edit.set_plain_text(&qs(comment));
     }
 
-    /// Set the sites
-    pub fn set_sites(&self, sites: Vec<&str>) {
-        unsafe {
-            let mut sites_cbox = self.sites_cbox;
-            sites_cbox.clear();
-            sites_cbox.add_item_q_string(&qs(DEFAULT_SITE));
-            for site in sites {
-                sites_cbox.add_item_q_string(&qs(site));
-            }
-        }
+    /// Retrieve the ticket, if any. Surrounding whitespace is trimmed.
+    pub unsafe fn ticket(&self) -> Option<String> {
+        let ticket = self.ticket_edit.text().to_std_string();
+        Some(ticket.trim().to_string()).filter(|ticket| !ticket.is_empty())
     }
 
-    /// set the list of rols
-    pub fn set_roles(&self, roles: Vec<&str>) {
-        unsafe {
-            let mut roles_list = self.roles_list;
-            roles_list.clear();
-            for role in roles {
-                roles_list.add_item_q_string(&qs(role));
-            }
-            roles_list.select_all();
-            roles_list.set_focus_policy(FocusPolicy::StrongFocus);
-        }
+    /// Set the ticket
+    pub unsafe fn set_ticket(&self, ticket: &str) {
+        let mut ticket_edit = self.ticket_edit;
+        ticket_edit.set_text(&qs(ticket));
     }
 
-    /// provide a vector of strings representing the sequences stored in the map
-    pub fn seqs(&self) -> Vec<String> {
-        self.levels
-            .keys()
-            .map(|x| x.to_string())
-            .collect::<Vec<_>>()
+    /// Set whether a comment must be given. The label is marked accordingly.
+    pub unsafe fn set_comment_required(&self, required: bool) {
+        self.comment_required.set(required);
+        let mut comment_label = self.comment_label;
+        comment_label.set_text(&qs(Self::field_label(tr("Comment"), required).as_str()));
     }
 
-    pub unsafe fn selected_seq(&self) -> Option<String> {
-        if !self.seqs_cbox.is_enabled() {
-            return None;
-        }
-        let result = self.seqs_cbox.current_text().to_std_string();
-        if result == DEFAULT_SEQ {
-            None
-        } else {
-            Some(result)
+    /// Set whether a ticket must be given. The label is marked accordingly.
+    pub unsafe fn set_ticket_required(&self, required: bool) {
+        self.ticket_required.set(required);
+        let mut ticket_label = self.ticket_label;
+        ticket_label.set_text(&qs(Self::field_label(tr("Ticket"), required).as_str()));
+    }
+
+    /// Set the regular expression the whole ticket must match, or None to accept any
+    /// ticket. The pattern is displayed as the tooltip of the ticket field.
+    pub unsafe fn set_ticket_pattern(&self, pattern: Option<&str>) -> Result<(), SelectionError> {
+        if let Some(pattern) = pattern {
+            ticket_regex(pattern)?;
         }
+        let tool_tip = pattern
+            .map(|pattern| trf("The ticket must match {}", &[&pattern]))
+            .unwrap_or_default();
+        let mut ticket_edit = self.ticket_edit;
+        ticket_edit.set_tool_tip(&qs(tool_tip.as_str()));
+        self.ticket_pattern.replace(pattern.map(str::to_string));
+        Ok(())
     }
 
-    pub unsafe fn selected_shot(&self) -> Option<String> {
-        if !self.shots_cbox.is_enabled() {
-            return None;
+    /// Retrieve the date (`YYYY-MM-DD`) from which the pins take effect, if specified
+    pub unsafe fn effective_from(&self) -> Option<String> {
+        Self::checked_date(self.effective_checkbox, self.effective_edit)
+    }
+
+    /// Set the date (`YYYY-MM-DD`) from which the pins take effect, or None for
+    /// immediately
+    pub unsafe fn set_effective_from(&self, date: Option<&str>) -> Result<(), SelectionError> {
+        Self::set_checked_date(self.effective_checkbox, self.effective_edit, date)
+    }
+
+    /// Retrieve the date (`YYYY-MM-DD`) on which the pins expire, if specified
+    pub unsafe fn expires_on(&self) -> Option<String> {
+        Self::checked_date(self.expiry_checkbox, self.expiry_edit)
+    }
+
+    /// Set the date (`YYYY-MM-DD`) on which the pins expire, or None for never
+    pub unsafe fn set_expires_on(&self, date: Option<&str>) -> Result<(), SelectionError> {
+        Self::set_checked_date(self.expiry_checkbox, self.expiry_edit, date)
+    }
+
+    /// Connect the slot to the signals emitted when either date changes
+    pub unsafe fn connect_dates_changed(&self, slot: &Slot<'a>) {
+        self.effective_checkbox.state_changed().connect(slot);
+        self.effective_edit.date_changed().connect(slot);
+        self.expiry_checkbox.state_changed().connect(slot);
+        self.expiry_edit.date_changed().connect(slot);
+    }
+
+    /// Return the selector to its initial state, clear the comment and ticket, and
+    /// leave the dates unspecified
+    pub unsafe fn reset(&self) {
+        self.selector.reset();
+        let mut comment_edit = self.comment_edit;
+        comment_edit.clear();
+        let mut ticket_edit = self.ticket_edit;
+        ticket_edit.clear();
+        let mut effective_checkbox = self.effective_checkbox;
+        effective_checkbox.set_checked(false);
+        let mut expiry_checkbox = self.expiry_checkbox;
+        expiry_checkbox.set_checked(false);
+    }
+
+    /// Set the pins already in place, against which the new pins are checked
+    /// for conflicts
+    pub unsafe fn set_existing_pins(&mut self, pins: Vec<PinSpec>) {
+        self.existing_pins = pins;
+        self.update_preview();
+    }
+
+    /// Retrieve the pins already in place
+    pub fn existing_pins(&self) -> &[PinSpec] {
+        self.existing_pins.as_slice()
+    }
+
+    /// Find the conflicts between the pins the selection expands to and the
+    /// existing pins
+    pub fn conflicts(&self, selection: &VpinSelection) -> Vec<Conflict> {
+        detect_conflicts(&selection.expand(), &self.existing_pins)
+    }
+
+    /// Resolve the pin currently in effect for the package of each selected
+    /// distribution, at the selected level, role and site
+    pub unsafe fn resolutions(&self) -> Vec<(String, Option<PinSpec>)> {
+        let mut packages = Vec::new();
+        for distribution in self.selector.selected_distributions() {
+            let package = package_name(distribution.as_str()).to_string();
+            if !packages.contains(&package) {
+                packages.push(package);
+            }
         }
-        let result = self.shots_cbox.current_text().to_std_string();
-        if result == DEFAULT_SHOT {
-            None
-        } else {
-            Some(result)
+        packages
+            .into_iter()
+            .map(|package| {
+                let query = self.selector.resolve_query(package.as_str());
+                let pin = resolve(&self.existing_pins, &query).cloned();
+                (package, pin)
+            })
+            .collect()
+    }
+
+    /// Compare each pin the selection expands to against the pin currently in
+    /// effect in its scope
+    pub fn version_changes(&self, selection: &VpinSelection) -> Vec<PinChange> {
+        version_changes(&selection.expand(), &self.existing_pins)
+    }
+
+    /// Return the toggled signal from the checkbox confirming downgrades
+    pub unsafe fn downgrade_toggled(&self) -> Signal<(bool,)> {
+        self.downgrade_checkbox.toggled()
+    }
+
+    /// List the pins the current selection expands to, highlighting those which
+    /// conflict with existing pins and badging each with its version change, and
+    /// display what the selected level currently resolves to, along with any warnings
+    /// about the selection. Any confirmation of
+    /// the conflicts is withdrawn, and any errors writing the pins are cleared, as the
+    /// selection may have changed. While any pin is a downgrade, OK and Apply are
+    /// disabled until the downgrade is confirmed.
+    pub unsafe fn update_preview(&self) {
+        self.conflicts_confirmed.set(false);
+        self.pin_errors.borrow_mut().clear();
+        self.render_preview();
+    }
+
+    /// Show or hide the loading indicator of a section. While the existing pins are
+    /// loading, conflicts cannot be detected, so OK and Apply are disabled, as is
+    /// Refresh, which would abandon them.
+    pub unsafe fn set_section_loading(&self, section: Section, loading: bool) {
+        if section != Section::ExistingPins {
+            self.selector.set_section_loading(section, loading);
+            return;
         }
+        self.loading_pins.set(loading);
+        let mut pins_loading = self.pins_loading;
+        pins_loading.set_visible(loading);
+        let mut refresh_button = self.refresh_button;
+        refresh_button.set_enabled(!loading);
+        self.render_preview();
+    }
+
+    /// Mark the pins which could not be written in the preview, along with the reason
+    pub unsafe fn set_pin_errors(&self, errors: Vec<(PinSpec, String)>) {
+        self.pin_errors.replace(errors);
+        self.render_preview();
     }
 
-    /// Return the selected Sequence/shot if applicable
-    pub unsafe fn selected_level(&self) -> Option<String> {
-        if !self.seq_shot_checkbox.is_checked() {
-            None
+    // populate the preview and the resolutions from the current selection
+    unsafe fn render_preview(&self) {
+        if self.existing_pins.is_empty() {
+            self.selector.set_resolutions(&[]);
         } else {
-            if let Some(seq) = self.selected_seq() {
-                if let Some(shot) = self.selected_shot() {
-                    Some(format!("{}.{}.{}", self.show.borrow(), seq, shot))
-                } else {
-                    Some(format!("{}.{}", self.show.borrow(), seq))
-                }
+            self.selector.set_resolutions(&self.resolutions());
+        }
+        let selection = self.selection();
+        let mut warning_label = self.warning_label;
+        let warnings = warnings(&selection)
+            .iter()
+            .map(|warning| warning.to_string())
+            .collect::<Vec<_>>();
+        warning_label.set_text(&qs(warnings.join("\n").as_str()));
+        warning_label.set_visible(!warnings.is_empty());
+        let pins = selection.expand();
+        let conflicts = detect_conflicts(&pins, &self.existing_pins);
+        let changes = version_changes(&pins, &self.existing_pins);
+        let mut preview = self.preview;
+        preview.clear();
+        for pin in &pins {
+            let pin_conflicts = conflicts
+                .iter()
+                .filter(|conflict| conflict.pin == *pin)
+                .collect::<Vec<_>>();
+            let pin_change = changes.iter().find(|change| change.pin == *pin);
+            let pin_errors = self.pin_errors.borrow();
+            let pin_error = pin_errors.iter().find(|(failed, _)| failed == pin);
+            let mut badges = Vec::new();
+            let mut details = Vec::new();
+            if let Some((_, error)) = pin_error {
+                badges.push(tr("failed"));
+                details.push(error.clone());
+            }
+            if let Some(change) = pin_change {
+                badges.push(Self::change_label(change));
+            }
+            if let Some(conflict) = pin_conflicts.first() {
+                badges.push(Self::conflict_label(conflict.kind));
+                details.extend(pin_conflicts.iter().map(|conflict| conflict.to_string()));
+            }
+            let text = if badges.is_empty() {
+                pin.to_string()
             } else {
-                None
+                format!("{}  [{}]", pin, badges.join(", "))
+            };
+            let mut item = QListWidgetItem::from_q_string(&qs(text.as_str()));
+            if !details.is_empty() {
+                item.set_tool_tip(&qs(details.join("\n").as_str()));
+            }
+            let downgrade = pin_change
+                .map(|change| change.change == VersionChange::Downgrade)
+                .unwrap_or(false);
+            if !pin_conflicts.is_empty() || downgrade || pin_error.is_some() {
+                let mut font = item.font();
+                font.set_bold(true);
+                item.set_font(&font);
             }
+            preview.add_item_q_list_widget_item(item.into_ptr());
         }
-    }
-    /// Given a new LevelMap, repalace the existing one
-    pub fn set_levels_map(&mut self, levels: LevelMap) {
-        std::mem::replace(&mut self.levels, levels);
-    }
-
-    // pub fn set_levels(&self, levels: Vec<String>) {
-    //     unsafe {
-    //         let mut seqs_cbox = self.seqs_cbox;
-    //         let mut shots_cbox = self.shots_cbox;
-    //         seqs_cbox.clear();
-    //         seqs_cbox.add_item_q_string(&qs(DEFAULT_SEQ));
-    //         for seq in levels {
-    //             seqs_cbox.add_item_q_string(&qs(seq));
-    //         }
-    //         shots_cbox.clear();
-    //         shots_cbox.add_item_q_string(&qs(DEFAULT_SHOT));
-    //     }
-    // }
-
-    pub fn set_levels_from_map(&self) {
-        unsafe {
-            let mut seqs_cbox = self.seqs_cbox;
-            let mut shots_cbox = self.shots_cbox;
-            seqs_cbox.clear();
-            seqs_cbox.add_item_q_string(&qs(DEFAULT_SEQ));
-            for seq in self.levels.keys() {
-                seqs_cbox.add_item_q_string(&qs(seq));
+        let has_downgrade = changes
+            .iter()
+            .any(|change| change.change == VersionChange::Downgrade);
+        let mut downgrade_checkbox = self.downgrade_checkbox;
+        if !has_downgrade && downgrade_checkbox.is_checked() {
+            // unchecking emits toggled, which refreshes the preview again
+            downgrade_checkbox.set_checked(false);
+        }
+        downgrade_checkbox.set_visible(has_downgrade);
+        let confirmed = !has_downgrade || downgrade_checkbox.is_checked();
+        let enabled = confirmed && !self.loading_pins.get();
+        self.ok_button().set_enabled(enabled);
+        let mut apply_button = self.apply_button;
+        apply_button.set_enabled(enabled);
+    }
+
+    /// Determine whether the pins the selection expands to may be created. When any
+    /// pin downgrades the version currently in effect, the user must first confirm
+    /// the downgrade via the checkbox.
+    ///
+    /// # Returns
+    /// * true if there are no downgrades, or the user has confirmed them
+    pub unsafe fn confirm_downgrades(&self, selection: &VpinSelection) -> bool {
+        let downgrade = self
+            .version_changes(selection)
+            .iter()
+            .any(|change| change.change == VersionChange::Downgrade);
+        if downgrade && !self.downgrade_checkbox.is_checked() {
+            self.set_error(Some(
+                tr("One or more pins downgrade the version currently in effect. Check \"Allow downgrade\" to continue.")
+                    .as_str(),
+            ));
+            return false;
+        }
+        true
+    }
+
+    /// Determine whether the pins the selection expands to may be created. When
+    /// they conflict with existing pins, the conflicts are reported and the user
+    /// must confirm by pressing the button again, without changing the selection.
+    ///
+    /// # Returns
+    /// * true if there are no conflicts, or the user has confirmed them
+    pub unsafe fn confirm_conflicts(&self, selection: &VpinSelection) -> bool {
+        let conflicts = self.conflicts(selection);
+        if conflicts.is_empty() || self.conflicts_confirmed.get() {
+            return true;
+        }
+        self.conflicts_confirmed.set(true);
+        let mut message = trf(
+            "{} conflict(s) with existing pins. Press again to create the pins anyway.",
+            &[&conflicts.len()],
+        );
+        for conflict in &conflicts {
+            message.push_str(format!("\n{}", conflict).as_str());
+        }
+        self.set_error(Some(message.as_str()));
+        false
+    }
+
+    /// Display an error message in the dialog, or hide the error label
+    /// when `error` is None
+    pub unsafe fn set_error(&self, error: Option<&str>) {
+        let mut error_label = self.error_label;
+        match error {
+            Some(error) => {
+                error_label.set_text(&qs(error));
+                error_label.set_visible(true);
+            }
+            None => {
+                error_label.clear();
+                error_label.set_visible(false);
             }
-            shots_cbox.clear();
-            shots_cbox.add_item_q_string(&qs(DEFAULT_SHOT));
         }
     }
 
-    pub unsafe fn clear_shots(&self) {
-        let mut shots_cbox = self.shots_cbox;
-        shots_cbox.clear();
-    }
-    /// Given a sequence from a selection, populate the shot combobox
-    pub unsafe fn set_shots_for_seq(&self, sequence: &str) {
-        let mut shots_cbox = self.shots_cbox;
-        shots_cbox.clear();
-        shots_cbox.add_item_q_string(&qs(DEFAULT_SHOT));
-        if let Some(shots) = self.levels.get(sequence) {
-            for shot in shots {
-                shots_cbox.add_item_q_string(&qs(shot));
+    /// Load the stylesheet. If `PBGUI_VPIN_STYLESHEET` names a stylesheet, it is
+    /// used in place of the built in one, and watched for changes if
+    /// `PBGUI_VPIN_WATCH_STYLESHEET` is also set. Should it fail to load, the error
+    /// is displayed and the built in stylesheet is used.
+    pub unsafe fn set_default_stylesheet(&mut self) {
+        if let Some(path) = stylesheet_path_from_env() {
+            let result = if watch_stylesheet_from_env() {
+                self.watch_stylesheet(&path)
+            } else {
+                self.set_stylesheet_from_path(&path)
+            };
+            match result {
+                Ok(()) => return,
+                Err(err) => self.set_error(Some(
+                    trf(
+                        "Unable to load the stylesheet {}: {}. Using the default",
+                        &[&path.display(), &err],
+                    )
+                    .as_str(),
+                )),
             }
         }
+        set_stylesheet_from_str(STYLE_STR, self.dialog.as_mut_ptr());
+    }
+
+    /// Apply one of the built in themes
+    pub unsafe fn set_theme(&mut self, theme: Theme) {
+        theme.apply(self.dialog.as_mut_ptr());
+    }
+
+    /// Load the stylesheet from the supplied path
+    pub unsafe fn set_stylesheet_from_path(&mut self, path: &Path) -> io::Result<()> {
+        apply_stylesheet_from_path(path, self.dialog.as_mut_ptr())
     }
 
-    pub unsafe fn set_roles_focus(&mut self) {
-        self.roles_filter.set_focus_0a();
+    /// Load the stylesheet from the supplied path, and re-apply it whenever
+    /// the file changes. Failures to reload are displayed in the dialog. This
+    /// replaces any stylesheet currently being watched.
+    pub unsafe fn watch_stylesheet(&mut self, path: &Path) -> io::Result<()> {
+        self.set_stylesheet_from_path(path)?;
+        let error_label = self.error_label;
+        self.stylesheet_watcher = Some(StylesheetWatcher::new(
+            path,
+            self.dialog.as_mut_ptr(),
+            move |path: &Path, err: io::Error| {
+                let mut error_label = error_label;
+                error_label.set_text(&qs(trf(
+                    "Unable to reload the stylesheet {}: {}",
+                    &[&path.display(), &err],
+                )
+                .as_str()));
+                error_label.set_visible(true);
+            },
+        ));
+        Ok(())
+    }
+
+    /// Stop watching the current stylesheet, if any
+    pub fn unwatch_stylesheet(&mut self) {
+        self.stylesheet_watcher = None;
     }
 
     /// display the dialog
@@ -399,149 +710,274 @@ impl<'a> InnerVpinDialog<'a> {
     pub fn finished(&self) -> qt_core::Signal<(std::os::raw::c_int,)> {
         self.dialog.finished()
     }
-    pub fn seqs_cb(&self) -> MutPtr<QComboBox> {
-        self.seqs_cbox
+
+    // label the dialog buttons for screen readers. The error label is announced
+    // as a description of the dialog's state. The tab order continues from the
+    // selector through the rest of the dialog
+    unsafe fn setup_accessibility(&self) {
+        set_accessible(
+            self.ok_button(),
+            tr("OK").as_str(),
+            tr("Create the version pin and close the dialog").as_str(),
+        );
+        set_accessible(
+            self.buttons.button(StandardButton::Cancel),
+            tr("Cancel").as_str(),
+            tr("Close the dialog without creating a version pin").as_str(),
+        );
+        set_accessible(
+            self.apply_button,
+            tr("Apply").as_str(),
+            tr("Create the version pin and keep the dialog open").as_str(),
+        );
+        set_accessible(
+            self.refresh_button,
+            tr("Refresh").as_str(),
+            tr("Fetch the roles, levels and sites again").as_str(),
+        );
+        set_accessible(
+            self.comment_edit,
+            tr("Comment").as_str(),
+            tr("Why the pin is needed. It is recorded with every pin").as_str(),
+        );
+        set_accessible(
+            self.ticket_edit,
+            tr("Ticket").as_str(),
+            tr("The ticket tracking the pin. It is recorded with every pin").as_str(),
+        );
+        set_accessible(
+            self.effective_checkbox,
+            tr("Effective from").as_str(),
+            tr("Check to choose the date from which the pin takes effect").as_str(),
+        );
+        set_accessible(
+            self.effective_edit,
+            tr("Effective date").as_str(),
+            tr("The date from which the pin takes effect").as_str(),
+        );
+        set_accessible(
+            self.expiry_checkbox,
+            tr("Expires on").as_str(),
+            tr("Check to choose the date on which the pin expires").as_str(),
+        );
+        set_accessible(
+            self.expiry_edit,
+            tr("Expiry date").as_str(),
+            tr("The date on which the pin expires").as_str(),
+        );
+        set_accessible(
+            self.warning_label,
+            tr("Warning").as_str(),
+            tr("Describes why the version pin may be a mistake").as_str(),
+        );
+        set_accessible(
+            self.preview,
+            tr("Pins").as_str(),
+            tr("The pins which will be created, and any conflicts with existing pins").as_str(),
+        );
+        set_accessible(
+            self.downgrade_checkbox,
+            tr("Allow downgrade").as_str(),
+            tr("Confirm that pinning an older version than the one in effect is intended").as_str(),
+        );
+        set_accessible(
+            self.error_label,
+            tr("Error").as_str(),
+            tr("Describes why the version pin could not be created").as_str(),
+        );
+        set_tab_chain(&self.interactive_widgets());
+    }
+
+    // add the multi-line field recording why the pins are needed, beneath a label
+    unsafe fn add_comment_edit(
+        mut parent: MutPtr<QVBoxLayout>,
+    ) -> (MutPtr<QLabel>, MutPtr<QPlainTextEdit>) {
+        let mut label = QLabel::from_q_string(&qs(tr("Comment").as_str()));
+        label.set_object_name(&qs("CommentLabel"));
+        let mut comment_edit = QPlainTextEdit::new();
+        comment_edit.set_object_name(&qs("CommentTextEdit"));
+        comment_edit.set_placeholder_text(&qs(tr("Why is this pin needed?").as_str()));
+        comment_edit.set_tab_changes_focus(true);
+        comment_edit.set_maximum_height(80);
+        let label_ptr = label.as_mut_ptr();
+        let comment_edit_ptr = comment_edit.as_mut_ptr();
+        label.set_buddy(comment_edit_ptr);
+        parent.add_widget(label.into_ptr());
+        parent.add_widget(comment_edit.into_ptr());
+        (label_ptr, comment_edit_ptr)
     }
 
-    unsafe fn add_site_cbox(mut parent: MutPtr<QLayout>) -> MutPtr<QComboBox> {
-        let mut sites_cbox = QComboBox::new_0a();
-        sites_cbox.set_object_name(&qs("SelectLocationComboBox"));
-        let sites_cbox_ptr = sites_cbox.as_mut_ptr();
-        parent.add_widget(sites_cbox.into_ptr());
-        sites_cbox_ptr
+    // add the field recording the ticket tracking the pins, beside its label
+    unsafe fn add_ticket_edit(
+        mut parent: MutPtr<QVBoxLayout>,
+    ) -> (MutPtr<QLabel>, MutPtr<QLineEdit>) {
+        let mut row = create_hlayout();
+        let mut label = QLabel::from_q_string(&qs(tr("Ticket").as_str()));
+        label.set_object_name(&qs("TicketLabel"));
+        let mut ticket_edit = QLineEdit::new();
+        ticket_edit.set_object_name(&qs("TicketLineEdit"));
+        ticket_edit.set_placeholder_text(&qs(tr("eg PROJ-123").as_str()));
+        let label_ptr = label.as_mut_ptr();
+        let ticket_edit_ptr = ticket_edit.as_mut_ptr();
+        label.set_buddy(ticket_edit_ptr);
+        row.add_widget(label.into_ptr());
+        row.add_widget(ticket_edit.into_ptr());
+        parent.add_layout_1a(row.into_ptr());
+        (label_ptr, ticket_edit_ptr)
+    }
+
+    // add a checkbox and date picker to the row. The date picker is disabled until
+    // the checkbox is checked
+    unsafe fn add_date_edit(
+        mut parent: MutPtr<QHBoxLayout>,
+        label: &str,
+        object_name: &str,
+        date: &QDate,
+    ) -> (MutPtr<QCheckBox>, MutPtr<QDateEdit>) {
+        let mut checkbox = QCheckBox::from_q_string(&qs(label));
+        checkbox.set_object_name(&qs(format!("{}CheckBox", object_name).as_str()));
+        let mut date_edit = QDateEdit::from_q_date(date);
+        date_edit.set_object_name(&qs(format!("{}DateEdit", object_name).as_str()));
+        date_edit.set_display_format(&qs(DATE_FORMAT));
+        date_edit.set_calendar_popup(true);
+        date_edit.set_enabled(false);
+        let checkbox_ptr = checkbox.as_mut_ptr();
+        let date_edit_ptr = date_edit.as_mut_ptr();
+        checkbox.toggled().connect(date_edit.slot_set_enabled());
+        parent.add_widget(checkbox.into_ptr());
+        parent.add_widget(date_edit.into_ptr());
+        (checkbox_ptr, date_edit_ptr)
+    }
+
+    // retrieve the date of the picker as YYYY-MM-DD, if its checkbox is checked
+    unsafe fn checked_date(
+        checkbox: MutPtr<QCheckBox>,
+        date_edit: MutPtr<QDateEdit>,
+    ) -> Option<String> {
+        if !checkbox.is_checked() {
+            return None;
+        }
+        Some(
+            date_edit
+                .date()
+                .to_string_q_string(&qs(DATE_FORMAT))
+                .to_std_string(),
+        )
+    }
+
+    // set the date of the picker from YYYY-MM-DD and check its checkbox, or uncheck
+    // the checkbox when the date is None
+    unsafe fn set_checked_date(
+        mut checkbox: MutPtr<QCheckBox>,
+        mut date_edit: MutPtr<QDateEdit>,
+        date: Option<&str>,
+    ) -> Result<(), SelectionError> {
+        let date = match date {
+            Some(date) => date,
+            None => {
+                checkbox.set_checked(false);
+                return Ok(());
+            }
+        };
+        let qdate = QDate::from_string_2_q_string(&qs(date), &qs(DATE_FORMAT));
+        if !qdate.is_valid() {
+            return Err(SelectionError::InvalidDate(date.to_string()));
+        }
+        date_edit.set_date(&qdate);
+        checkbox.set_checked(true);
+        Ok(())
+    }
+
+    // label a field, marking it when it must be filled in
+    fn field_label(label: String, required: bool) -> String {
+        if required {
+            trf("{} (required)", &[&label])
+        } else {
+            label
+        }
     }
 
-    unsafe fn add_select_site_groupbox(mut parent: MutPtr<QVBoxLayout>) -> MutPtr<QGroupBox> {
-        let mut label = QLabel::from_q_string(&qs("Select Site"));
-        label.set_object_name(&qs("SelectSiteLabel"));
+    // add the list previewing the pins the selection expands to, beneath a label
+    unsafe fn add_preview(mut parent: MutPtr<QVBoxLayout>) -> MutPtr<QListWidget> {
+        let mut label = QLabel::from_q_string(&qs(tr("Pins").as_str()));
+        label.set_object_name(&qs("PinPreviewLabel"));
+        let mut preview = QListWidget::new_0a();
+        preview.set_object_name(&qs("PinPreviewListWidget"));
+        preview.set_selection_mode(SelectionMode::NoSelection);
+        let preview_ptr = preview.as_mut_ptr();
+        label.set_buddy(preview_ptr);
         parent.add_widget(label.into_ptr());
-        let mut group_box = QGroupBox::new();
-        let group_box_ptr = group_box.as_mut_ptr();
-        group_box.set_object_name(&qs("SelectSiteGroupBox"));
-        let layout = create_vlayout();
-        group_box.set_layout(layout.into_ptr());
-        parent.add_widget(group_box.into_ptr());
-        group_box_ptr
-    }
-
-    unsafe fn add_seq_shot_checkbox(mut parent: MutPtr<QVBoxLayout>) -> MutPtr<QCheckBox> {
-        let mut cb = QCheckBox::from_q_string(&qs("Specify Seq(s) / Shot(s)"));
-        cb.set_object_name(&qs("SeqShotCheckBox"));
-        let cb_ptr = cb.as_mut_ptr();
-        parent.add_widget(cb.into_ptr());
-        cb_ptr
-    }
-
-    unsafe fn add_seq_cbox(mut parent: MutPtr<QLayout>) -> MutPtr<QComboBox> {
-        let mut seqs_cbox = QComboBox::new_0a();
-        seqs_cbox.set_object_name(&qs("AddSeqsComboBox"));
-        seqs_cbox.add_item_q_string(&qs("All Sequences"));
-        let seqs_cbox_ptr = seqs_cbox.as_mut_ptr();
-        parent.add_widget(seqs_cbox.into_ptr());
-        seqs_cbox_ptr
-    }
-
-    unsafe fn add_shot_cbox(mut parent: MutPtr<QLayout>) -> MutPtr<QComboBox> {
-        let mut shots_cbox = QComboBox::new_0a();
-        shots_cbox.set_object_name(&qs("AddShotsComboBox"));
-        shots_cbox.add_item_q_string(&qs("All Shots"));
-        let shots_cbox_ptr = shots_cbox.as_mut_ptr();
-        parent.add_widget(shots_cbox.into_ptr());
-        shots_cbox_ptr
-    }
-
-    unsafe fn add_select_level_groupbox(mut parent: MutPtr<QVBoxLayout>) -> MutPtr<QGroupBox> {
-        let mut group_box = QGroupBox::new();
-        let group_box_ptr = group_box.as_mut_ptr();
-        group_box.set_object_name(&qs("SelectLevelsGroupBox"));
-        let layout = create_vlayout();
-        group_box.set_layout(layout.into_ptr());
-        parent.add_widget(group_box.into_ptr());
-        group_box_ptr
-    }
-
-    unsafe fn add_roles_checkbox(mut parent: MutPtr<QVBoxLayout>) -> MutPtr<QCheckBox> {
-        let mut cb = QCheckBox::from_q_string(&qs("Specify Roles"));
-        let cb_ptr = cb.as_mut_ptr();
-        parent.add_widget(cb.into_ptr());
-        cb_ptr
-    }
-    unsafe fn add_roles_listwidget(mut parent: MutPtr<QLayout>) -> MutPtr<QListWidget> {
-        let mut list_widget = QListWidget::new_0a();
-        list_widget.set_selection_mode(SelectionMode::ExtendedSelection);
-        let list_widget_ptr = list_widget.as_mut_ptr();
-        parent.add_widget(list_widget.into_ptr());
-        list_widget_ptr
-    }
-
-    unsafe fn add_roles_filter(parent: MutPtr<QLayout>) -> MutPtr<QLineEdit> {
-        let mut hlayout = create_hlayout();
-        let mut hlayout_ptr = hlayout.as_mut_ptr();
-        let mut parent: MutPtr<QVBoxLayout> = parent.dynamic_cast_mut();
-        if parent.is_null() {
-            panic!("unable to cast layout");
+        parent.add_widget(preview.into_ptr());
+        preview_ptr
+    }
+
+    // add the busy indicator shown beneath the preview while the existing pins load
+    unsafe fn add_pins_loading_indicator(mut parent: MutPtr<QVBoxLayout>) -> MutPtr<QProgressBar> {
+        let mut indicator = QProgressBar::new_0a();
+        indicator.set_object_name(&qs("PinsLoadingIndicator"));
+        // a range of 0..0 makes the progress bar a busy indicator
+        indicator.set_range(0, 0);
+        indicator.set_text_visible(false);
+        indicator.set_visible(false);
+        let indicator_ptr = indicator.as_mut_ptr();
+        set_accessible(
+            indicator_ptr,
+            tr("Loading pins").as_str(),
+            tr("Shown while the data is loading").as_str(),
+        );
+        parent.add_widget(indicator.into_ptr());
+        indicator_ptr
+    }
+
+    // add the checkbox confirming downgrades. It is hidden until a pin is a downgrade
+    unsafe fn add_downgrade_checkbox(mut parent: MutPtr<QVBoxLayout>) -> MutPtr<QCheckBox> {
+        let mut checkbox = QCheckBox::from_q_string(&qs(tr("Allow downgrade").as_str()));
+        checkbox.set_object_name(&qs("AllowDowngradeCheckBox"));
+        checkbox.set_visible(false);
+        let checkbox_ptr = checkbox.as_mut_ptr();
+        parent.add_widget(checkbox.into_ptr());
+        checkbox_ptr
+    }
+
+    // label a version change for display alongside the pin in the preview
+    fn change_label(change: &PinChange) -> String {
+        match change.change {
+            VersionChange::Upgrade => trf("upgrade from {}", &[&change.current.distribution]),
+            VersionChange::Downgrade => trf("downgrade from {}", &[&change.current.distribution]),
+            VersionChange::NoOp => tr("no change"),
         }
-        parent.add_layout_1a(hlayout.into_ptr());
-        hlayout_ptr.add_widget(QLabel::from_q_string(&qs("Filter:")).into_ptr());
-        let mut line_edit = QLineEdit::new();
-        line_edit.set_object_name(&qs("RolesFilterLineEdit"));
-        let line_edit_ptr = line_edit.as_mut_ptr();
-        hlayout_ptr.add_widget(line_edit.into_ptr());
-        line_edit_ptr
-    }
-
-    unsafe fn add_select_roles_groupbox(mut parent: MutPtr<QVBoxLayout>) -> MutPtr<QGroupBox> {
-        let mut group_box = QGroupBox::new();
-        let mut group_box_ptr = group_box.as_mut_ptr();
-        group_box.set_object_name(&qs("SelectRolesGroupBox"));
-        parent.add_widget(group_box.into_ptr());
-        let layout = create_vlayout();
-        group_box_ptr.set_layout(layout.into_ptr());
-        // we default to disabled
-        group_box_ptr.set_enabled(false);
-        group_box_ptr
-    }
-    unsafe fn add_right_layout(mut parent: MutPtr<QHBoxLayout>) -> MutPtr<QVBoxLayout> {
-        let mut layout_right = create_vlayout();
-        let layout_right_ptr = layout_right.as_mut_ptr();
-        parent.add_layout_1a(layout_right.into_ptr());
-        layout_right_ptr
-    }
-    unsafe fn add_left_layout(mut parent: MutPtr<QHBoxLayout>) -> MutPtr<QVBoxLayout> {
-        let mut layout_left = create_vlayout();
-        let layout_left_ptr = layout_left.as_mut_ptr();
-        parent.add_layout_1a(layout_left.into_ptr());
-        layout_left_ptr
-    }
-    // add the add_entry label to the left hand side
-    unsafe fn add_entry_label(mut parent: MutPtr<QVBoxLayout>) {
-        // add label
-        let mut entry_frame = QFrame::new_0a();
-        let mut entry_frame_ptr = entry_frame.as_mut_ptr();
-        parent.add_widget(entry_frame.into_ptr());
-        let mut add_entries = QLabel::from_q_string(&qs("Add Entry"));
-        add_entries.set_object_name(&qs("AddEntriesLabel"));
-        let mut add_entry_layout = create_vlayout();
-        add_entry_layout.add_widget(add_entries.into_ptr());
-        entry_frame_ptr.set_layout(add_entry_layout.into_ptr());
-    }
-
-    // add the distribution label in the middle of the dialog
-    unsafe fn add_distribution_label(
-        distribution: &str,
-        mut parent: MutPtr<QVBoxLayout>,
-    ) -> MutPtr<QLabel> {
-        // layout is the top level layout for the dialog
-        let mut dist_frame = QFrame::new_0a();
-        let mut distribution = QLabel::from_q_string(&qs(distribution));
-        let distribution_ptr = distribution.as_mut_ptr();
-        distribution.set_object_name(&qs("DistributionLabel"));
-        let mut add_entry_layout = create_hlayout();
-        add_entry_layout.add_stretch_1a(1);
-        add_entry_layout.add_widget(distribution.into_ptr());
-        add_entry_layout.add_stretch_1a(1);
-        dist_frame.set_layout(add_entry_layout.into_ptr());
-        parent.add_widget(dist_frame.into_ptr());
-        distribution_ptr
+    }
+
+    // label a conflict for display alongside the pin in the preview
+    fn conflict_label(kind: ConflictKind) -> String {
+        match kind {
+            ConflictKind::Duplicate => tr("duplicate"),
+            ConflictKind::Shadows => tr("shadows an existing pin"),
+            ConflictKind::ShadowedBy => tr("shadowed by an existing pin"),
+        }
+    }
+
+    // add the label used to warn the user about a selection which may be a mistake.
+    // It stays hidden until there is something to report
+    unsafe fn add_warning_label(mut parent: MutPtr<QVBoxLayout>) -> MutPtr<QLabel> {
+        let mut warning_label = QLabel::new();
+        warning_label.set_object_name(&qs("VpinWarningLabel"));
+        warning_label.set_word_wrap(true);
+        warning_label.set_visible(false);
+        let warning_label_ptr = warning_label.as_mut_ptr();
+        parent.add_widget(warning_label.into_ptr());
+        warning_label_ptr
+    }
+
+    // add the label used to report errors back to the user. It stays hidden
+    // until there is something to report
+    unsafe fn add_error_label(mut parent: MutPtr<QVBoxLayout>) -> MutPtr<QLabel> {
+        let mut error_label = QLabel::new();
+        error_label.set_object_name(&qs("VpinErrorLabel"));
+        error_label.set_word_wrap(true);
+        error_label.set_visible(false);
+        let error_label_ptr = error_label.as_mut_ptr();
+        parent.add_widget(error_label.into_ptr());
+        error_label_ptr
     }
 }
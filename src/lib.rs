@@ -1,2 +1,26 @@
+pub mod accessibility;
+pub mod cache;
+pub mod choice;
+pub mod conflict;
+pub mod data_provider;
+pub mod distribution;
+#[cfg(feature = "http")]
+pub mod http_client;
+pub mod i18n;
 pub(crate) mod inner_vpin_dialog;
+pub mod loader;
+pub mod pin_provider;
+pub mod pin_sink;
+pub mod pin_spec;
+pub mod resolver;
+pub mod shortcuts;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_store;
+pub mod stylesheet;
+pub mod theme;
+pub mod validation;
 pub mod vpin_dialog;
+pub mod vpin_prompt;
+pub mod vpin_selection;
+pub mod vpin_selector_widget;
+pub use crate::vpin_prompt::{prompt, PromptOptions};
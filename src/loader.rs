@@ -0,0 +1,452 @@
+//! Loading of the dialog's data on a worker thread, so that slow providers do not
+//! freeze the dialog. The provider is moved to the worker, which sends the result of
+//! each request back over a channel. The Loader lives on the GUI thread, where the
+//! dialog polls it and applies the results as they arrive.
+//!
+//! Cancelling the Loader discards any results still to come. A request already in
+//! flight runs to completion on the worker, but its result is dropped.
+use crate::data_provider::DataProvider;
+use crate::pin_provider::PinProvider;
+use crate::pin_sink::SinkError;
+use crate::pin_spec::PinSpec;
+use crate::vpin_selection::LevelMap;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+
+/// Section identifies the part of the dialog a request fills in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Section {
+    Roles,
+    Levels,
+    Sites,
+    Platforms,
+    ExistingPins,
+}
+
+/// Loaded holds the data retrieved for a Section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Loaded {
+    Roles(Vec<String>),
+    Levels(LevelMap),
+    Sites(Vec<String>),
+    Platforms(Vec<String>),
+    ExistingPins(Vec<PinSpec>),
+}
+
+/// LoadResult is the outcome of the request for a Section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadResult {
+    /// The Section the request fills in
+    pub section: Section,
+    /// The data, or the error message should the request have failed
+    pub result: Result<Loaded, String>,
+}
+
+/// Loader runs provider requests on worker threads, and collects their results
+pub struct Loader {
+    sender: Sender<LoadResult>,
+    receiver: Receiver<LoadResult>,
+    cancelled: Arc<AtomicBool>,
+    pending: HashSet<Section>,
+}
+
+impl Loader {
+    /// Create a Loader with nothing to load
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Loader instance
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+        Loader {
+            sender,
+            receiver,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            pending: HashSet::new(),
+        }
+    }
+
+    /// Load the roles, sites, platforms, and the sequences and shots of the show, on a
+    /// worker thread
+    ///
+    /// # Arguments
+    /// * `provider` - The DataProvider, which is moved to the worker
+    /// * `show` - The name of the show
+    ///
+    /// # Returns
+    /// * None
+    pub fn load_data<P>(&mut self, provider: P, show: &str)
+    where
+        P: DataProvider + Send + 'static,
+    {
+        self.load_data_with(provider, show, |_| Ok(()));
+    }
+
+    /// Load the data as `load_data` does, first preparing the provider on the worker,
+    /// eg to empty a cache without blocking the calling thread. Should the provider
+    /// fail to be prepared, the error is reported for every section.
+    ///
+    /// # Arguments
+    /// * `provider` - The DataProvider, which is moved to the worker
+    /// * `show` - The name of the show
+    /// * `prepare` - Closure run on the worker, before the data is requested
+    ///
+    /// # Returns
+    /// * None
+    pub fn load_data_with<P, F>(&mut self, provider: P, show: &str, prepare: F)
+    where
+        P: DataProvider + Send + 'static,
+        F: FnOnce(&mut P) -> Result<(), SinkError> + Send + 'static,
+    {
+        self.pending.extend(&[
+            Section::Roles,
+            Section::Sites,
+            Section::Platforms,
+            Section::Levels,
+        ]);
+        let sender = self.sender.clone();
+        let cancelled = self.cancelled.clone();
+        let show = show.to_string();
+        thread::spawn(move || {
+            let mut provider = provider;
+            if let Err(err) = prepare(&mut provider) {
+                let error = err.to_string();
+                for section in &[
+                    Section::Roles,
+                    Section::Sites,
+                    Section::Platforms,
+                    Section::Levels,
+                ] {
+                    let error = error.clone();
+                    if !send(&sender, &cancelled, *section, || Err(error.into())) {
+                        break;
+                    }
+                }
+                return;
+            }
+            let _ = send(&sender, &cancelled, Section::Roles, || {
+                provider.roles().map(Loaded::Roles)
+            }) && send(&sender, &cancelled, Section::Sites, || {
+                provider.sites().map(Loaded::Sites)
+            }) && send(&sender, &cancelled, Section::Platforms, || {
+                provider.platforms().map(Loaded::Platforms)
+            }) && send(&sender, &cancelled, Section::Levels, || {
+                provider.levels(show.as_str()).map(Loaded::Levels)
+            });
+        });
+    }
+
+    /// Load the pins already in place for the show, on a worker thread
+    ///
+    /// # Arguments
+    /// * `provider` - The PinProvider, which is moved to the worker
+    /// * `show` - The name of the show
+    ///
+    /// # Returns
+    /// * None
+    pub fn load_existing_pins<P>(&mut self, provider: P, show: &str)
+    where
+        P: PinProvider + Send + 'static,
+    {
+        self.pending.insert(Section::ExistingPins);
+        let sender = self.sender.clone();
+        let cancelled = self.cancelled.clone();
+        let show = show.to_string();
+        thread::spawn(move || {
+            let mut provider = provider;
+            send(&sender, &cancelled, Section::ExistingPins, || {
+                provider
+                    .existing_pins(show.as_str())
+                    .map(Loaded::ExistingPins)
+            });
+        });
+    }
+
+    /// Collect the results which have arrived since the last poll, without blocking
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of LoadResult
+    pub fn poll(&mut self) -> Vec<LoadResult> {
+        let results = self.receiver.try_iter().collect::<Vec<_>>();
+        for result in &results {
+            self.pending.remove(&result.section);
+        }
+        results
+    }
+
+    /// Determine whether the Section is waiting on a request
+    ///
+    /// # Arguments
+    /// * `section` - The Section
+    ///
+    /// # Returns
+    /// * bool
+    pub fn is_loading(&self, section: Section) -> bool {
+        self.pending.contains(&section)
+    }
+
+    /// Determine whether every request has delivered its result
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn is_idle(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Discard the results of every request made so far. Subsequent loads are
+    /// unaffected.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn cancel(&mut self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.cancelled = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = channel();
+        self.sender = sender;
+        self.receiver = receiver;
+        self.pending.clear();
+    }
+}
+
+impl Default for Loader {
+    fn default() -> Self {
+        Loader::new()
+    }
+}
+
+impl Drop for Loader {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+}
+
+// Run the request unless the load has been cancelled, and send its result. Returns
+// false once the load has been cancelled, so that the worker stops.
+fn send<F>(
+    sender: &Sender<LoadResult>,
+    cancelled: &AtomicBool,
+    section: Section,
+    request: F,
+) -> bool
+where
+    F: FnOnce() -> Result<Loaded, SinkError>,
+{
+    if cancelled.load(Ordering::SeqCst) {
+        return false;
+    }
+    let result = request().map_err(|err| err.to_string());
+    !cancelled.load(Ordering::SeqCst) && sender.send(LoadResult { section, result }).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pin_sink::MemorySink;
+    use std::sync::mpsc::Receiver;
+    use std::time::{Duration, Instant};
+
+    // GatedProvider reports each request as it is made, and holds the roles request
+    // until the test opens the gate. It reports being dropped once the worker is done.
+    struct GatedProvider {
+        gate: Receiver<()>,
+        calls: Sender<&'static str>,
+    }
+
+    impl DataProvider for GatedProvider {
+        fn roles(&mut self) -> Result<Vec<String>, SinkError> {
+            self.calls.send("roles").ok();
+            self.gate.recv().ok();
+            Ok(vec!["anim".to_string()])
+        }
+
+        fn levels(&mut self, _show: &str) -> Result<LevelMap, SinkError> {
+            self.calls.send("levels").ok();
+            Ok(LevelMap::new())
+        }
+
+        fn sites(&mut self) -> Result<Vec<String>, SinkError> {
+            self.calls.send("sites").ok();
+            Err("the sites are unavailable".into())
+        }
+
+        fn distributions(&mut self, _package: &str) -> Result<Vec<String>, SinkError> {
+            Ok(Vec::new())
+        }
+    }
+
+    impl Drop for GatedProvider {
+        fn drop(&mut self) {
+            self.calls.send("dropped").ok();
+        }
+    }
+
+    // Create a provider along with the sender opening its gate, and the receiver
+    // reporting its requests
+    fn gated_provider() -> (GatedProvider, Sender<()>, Receiver<&'static str>) {
+        let (gate_sender, gate) = channel();
+        let (calls, call_receiver) = channel();
+        (GatedProvider { gate, calls }, gate_sender, call_receiver)
+    }
+
+    // Poll the loader until every request has delivered its result
+    fn wait_until_idle(loader: &mut Loader) -> Vec<LoadResult> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut results = Vec::new();
+        while !loader.is_idle() {
+            assert!(Instant::now() < deadline, "the loader did not finish");
+            results.extend(loader.poll());
+            thread::sleep(Duration::from_millis(5));
+        }
+        results
+    }
+
+    fn next_call(calls: &Receiver<&'static str>) -> &'static str {
+        calls
+            .recv_timeout(Duration::from_secs(5))
+            .expect("the provider was not called")
+    }
+
+    #[test]
+    fn delivers_each_section_as_it_loads() {
+        let (provider, gate, calls) = gated_provider();
+        let mut loader = Loader::new();
+        loader.load_data(provider, "DEV01");
+        assert_eq!(next_call(&calls), "roles");
+        assert!(loader.is_loading(Section::Roles));
+        assert!(loader.is_loading(Section::Platforms));
+        assert!(loader.poll().is_empty());
+        gate.send(()).unwrap();
+        let results = wait_until_idle(&mut loader);
+        assert_eq!(
+            results,
+            vec![
+                LoadResult {
+                    section: Section::Roles,
+                    result: Ok(Loaded::Roles(vec!["anim".to_string()])),
+                },
+                LoadResult {
+                    section: Section::Sites,
+                    result: Err("the sites are unavailable".to_string()),
+                },
+                LoadResult {
+                    section: Section::Platforms,
+                    result: Ok(Loaded::Platforms(Vec::new())),
+                },
+                LoadResult {
+                    section: Section::Levels,
+                    result: Ok(Loaded::Levels(LevelMap::new())),
+                },
+            ]
+        );
+        assert!(!loader.is_loading(Section::Roles));
+    }
+
+    #[test]
+    fn drops_the_results_of_a_cancelled_load() {
+        let (provider, gate, calls) = gated_provider();
+        let mut loader = Loader::new();
+        loader.load_data(provider, "DEV01");
+        assert_eq!(next_call(&calls), "roles");
+        loader.cancel();
+        assert!(loader.is_idle());
+        // the request in flight completes, but the worker stops without making more
+        gate.send(()).unwrap();
+        assert_eq!(next_call(&calls), "dropped");
+        assert!(loader.poll().is_empty());
+
+        // loads made after cancelling are delivered
+        let mut sink = MemorySink::new();
+        crate::pin_sink::write_pins(
+            &mut sink,
+            &[PinSpec {
+                distribution: "modelpublish-1.2.0".to_string(),
+                level: "DEV01".to_string(),
+                role: None,
+                site: None,
+                platform: None,
+                comment: None,
+                ticket: None,
+                effective_from: None,
+                expires_on: None,
+            }],
+        )
+        .unwrap();
+        loader.load_existing_pins(sink.clone(), "DEV01");
+        let results = wait_until_idle(&mut loader);
+        assert_eq!(
+            results,
+            vec![LoadResult {
+                section: Section::ExistingPins,
+                result: Ok(Loaded::ExistingPins(sink.pins().to_vec())),
+            }]
+        );
+    }
+
+    #[test]
+    fn prepares_the_provider_on_the_worker() {
+        let (provider, gate, calls) = gated_provider();
+        let mut loader = Loader::new();
+        loader.load_data_with(provider, "DEV01", |provider| {
+            provider.calls.send("prepared").ok();
+            Ok(())
+        });
+        assert_eq!(next_call(&calls), "prepared");
+        assert_eq!(next_call(&calls), "roles");
+        gate.send(()).unwrap();
+        assert_eq!(wait_until_idle(&mut loader).len(), 4);
+    }
+
+    #[test]
+    fn reports_a_failure_to_prepare_for_every_section() {
+        let (provider, _gate, calls) = gated_provider();
+        let mut loader = Loader::new();
+        loader.load_data_with(
+            provider,
+            "DEV01",
+            |_| Err("the cache is unavailable".into()),
+        );
+        let results = wait_until_idle(&mut loader);
+        let sections = results
+            .iter()
+            .map(|result| result.section)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sections,
+            vec![
+                Section::Roles,
+                Section::Sites,
+                Section::Platforms,
+                Section::Levels
+            ]
+        );
+        assert!(results
+            .iter()
+            .all(|result| result.result == Err("the cache is unavailable".to_string())));
+        assert_eq!(next_call(&calls), "dropped");
+    }
+
+    #[test]
+    fn stops_the_worker_when_dropped() {
+        let (provider, gate, calls) = gated_provider();
+        let mut loader = Loader::new();
+        loader.load_data(provider, "DEV01");
+        assert_eq!(next_call(&calls), "roles");
+        drop(loader);
+        gate.send(()).unwrap();
+        assert_eq!(next_call(&calls), "dropped");
+    }
+}
@@ -0,0 +1,29 @@
+//! The PinProvider trait abstracts over the source of the pins already in place,
+//! against which the dialog reports conflicts and resolves the current version.
+use crate::data_provider::lock;
+use crate::pin_sink::{MemorySink, SinkError};
+use crate::pin_spec::PinSpec;
+use std::sync::{Arc, Mutex};
+
+/// PinProvider is implemented by stores which can list the existing pins for a show
+pub trait PinProvider {
+    /// Retrieve every pin for the show, at the show level or beneath it
+    fn existing_pins(&mut self, show: &str) -> Result<Vec<PinSpec>, SinkError>;
+}
+
+impl PinProvider for MemorySink {
+    fn existing_pins(&mut self, show: &str) -> Result<Vec<PinSpec>, SinkError> {
+        Ok(self
+            .pins()
+            .iter()
+            .filter(|pin| pin.show() == show)
+            .cloned()
+            .collect())
+    }
+}
+
+impl<P: PinProvider> PinProvider for Arc<Mutex<P>> {
+    fn existing_pins(&mut self, show: &str) -> Result<Vec<PinSpec>, SinkError> {
+        lock(self)?.existing_pins(show)
+    }
+}
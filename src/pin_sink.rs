@@ -0,0 +1,328 @@
+//! The PinSink trait abstracts over the destination of new pins, so that the
+//! VpinDialog can write the pins a selection expands to without knowing where
+//! they are stored. Pins are written within a transaction: either every pin is
+//! committed, or the sink is rolled back.
+use crate::i18n::trf;
+use crate::pin_spec::PinSpec;
+use std::error::Error;
+use std::fmt;
+
+/// The error type returned by PinSink methods
+pub type SinkError = Box<dyn Error>;
+
+/// PinSink is implemented by stores which can write pins transactionally
+pub trait PinSink {
+    /// Start a transaction
+    fn begin(&mut self) -> Result<(), SinkError>;
+    /// Write a pin within the current transaction
+    fn write(&mut self, pin: &PinSpec) -> Result<(), SinkError>;
+    /// Make the pins written since `begin` permanent
+    fn commit(&mut self) -> Result<(), SinkError>;
+    /// Discard the pins written since `begin`
+    fn rollback(&mut self) -> Result<(), SinkError>;
+}
+
+/// CommitError describes why the pins could not be written
+#[derive(Debug)]
+pub enum CommitError {
+    /// The transaction could not be started
+    Begin(SinkError),
+    /// A pin could not be written. The transaction was rolled back, and the
+    /// rollback error is recorded should that have failed too.
+    Write {
+        pin: Box<PinSpec>,
+        error: SinkError,
+        rollback: Option<SinkError>,
+    },
+    /// The transaction could not be committed. The transaction was rolled back,
+    /// and the rollback error is recorded should that have failed too.
+    Commit {
+        error: SinkError,
+        rollback: Option<SinkError>,
+    },
+}
+
+impl CommitError {
+    /// Retrieve the pins which failed to be written, along with the reason
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of (PinSpec, error message) tuples
+    pub fn pin_errors(&self) -> Vec<(PinSpec, String)> {
+        match self {
+            CommitError::Write { pin, error, .. } => vec![((**pin).clone(), error.to_string())],
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for CommitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (msg, rollback) = match self {
+            CommitError::Begin(error) => (trf("Unable to begin writing pins: {}", &[error]), None),
+            CommitError::Write {
+                pin,
+                error,
+                rollback,
+            } => (
+                trf(
+                    "Unable to write {}: {}. No pins were written.",
+                    &[pin, error],
+                ),
+                rollback.as_ref(),
+            ),
+            CommitError::Commit { error, rollback } => (
+                trf("Unable to commit pins: {}. No pins were written.", &[error]),
+                rollback.as_ref(),
+            ),
+        };
+        match rollback {
+            Some(rollback) => write!(f, "{} {}", msg, trf("Rollback failed: {}", &[rollback])),
+            None => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl Error for CommitError {}
+
+/// Write the pins through the sink within a single transaction. Should any pin
+/// fail to be written, or the transaction fail to commit, the transaction is
+/// rolled back.
+///
+/// # Arguments
+/// * `sink` - The PinSink
+/// * `pins` - The pins to write
+///
+/// # Returns
+/// * Ok if every pin was committed
+/// * CommitError describing the failure otherwise
+pub fn write_pins<S: PinSink + ?Sized>(sink: &mut S, pins: &[PinSpec]) -> Result<(), CommitError> {
+    sink.begin().map_err(CommitError::Begin)?;
+    for pin in pins {
+        if let Err(error) = sink.write(pin) {
+            return Err(CommitError::Write {
+                pin: Box::new(pin.clone()),
+                error,
+                rollback: sink.rollback().err(),
+            });
+        }
+    }
+    if let Err(error) = sink.commit() {
+        return Err(CommitError::Commit {
+            error,
+            rollback: sink.rollback().err(),
+        });
+    }
+    Ok(())
+}
+
+/// MemorySink holds pins in memory. It is useful for prototyping, and for
+/// collecting the pins written by the dialog.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemorySink {
+    pins: Vec<PinSpec>,
+    pending: Option<Vec<PinSpec>>,
+}
+
+impl MemorySink {
+    /// Create an empty MemorySink
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * MemorySink instance
+    pub fn new() -> Self {
+        MemorySink::default()
+    }
+
+    /// Retrieve the committed pins
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * The pins as a slice
+    pub fn pins(&self) -> &[PinSpec] {
+        self.pins.as_slice()
+    }
+
+    // retrieve the pending pins, failing outside of a transaction
+    fn pending(&mut self) -> Result<&mut Vec<PinSpec>, SinkError> {
+        self.pending
+            .as_mut()
+            .ok_or_else(|| "no transaction in progress".into())
+    }
+}
+
+impl PinSink for MemorySink {
+    fn begin(&mut self) -> Result<(), SinkError> {
+        if self.pending.is_some() {
+            return Err("transaction already in progress".into());
+        }
+        self.pending = Some(Vec::new());
+        Ok(())
+    }
+
+    fn write(&mut self, pin: &PinSpec) -> Result<(), SinkError> {
+        self.pending()?.push(pin.clone());
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), SinkError> {
+        let pending = self.pending()?.split_off(0);
+        self.pins.extend(pending);
+        self.pending = None;
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<(), SinkError> {
+        self.pending()?;
+        self.pending = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // FailingSink wraps a MemorySink, failing the requested operations
+    #[derive(Default)]
+    struct FailingSink {
+        sink: MemorySink,
+        fail_write: Option<String>,
+        fail_commit: bool,
+        fail_rollback: bool,
+        rolled_back: bool,
+    }
+
+    impl PinSink for FailingSink {
+        fn begin(&mut self) -> Result<(), SinkError> {
+            self.sink.begin()
+        }
+
+        fn write(&mut self, pin: &PinSpec) -> Result<(), SinkError> {
+            if self.fail_write.as_ref() == Some(&pin.distribution) {
+                return Err("disk full".into());
+            }
+            self.sink.write(pin)
+        }
+
+        fn commit(&mut self) -> Result<(), SinkError> {
+            if self.fail_commit {
+                return Err("database is locked".into());
+            }
+            self.sink.commit()
+        }
+
+        fn rollback(&mut self) -> Result<(), SinkError> {
+            self.rolled_back = true;
+            self.sink.rollback()?;
+            if self.fail_rollback {
+                return Err("connection lost".into());
+            }
+            Ok(())
+        }
+    }
+
+    fn pins() -> Vec<PinSpec> {
+        ["modelpublish-1.2.0", "modelpublish-plugin-1.2.0"]
+            .iter()
+            .map(|distribution| PinSpec {
+                distribution: distribution.to_string(),
+                level: "DEV01".to_string(),
+                role: None,
+                site: None,
+                platform: None,
+                comment: None,
+                ticket: None,
+                effective_from: None,
+                expires_on: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn commits_every_pin() {
+        let mut sink = MemorySink::new();
+        assert!(write_pins(&mut sink, &pins()).is_ok());
+        assert_eq!(sink.pins(), pins().as_slice());
+    }
+
+    #[test]
+    fn rolls_back_when_a_pin_cannot_be_written() {
+        let mut sink = FailingSink {
+            fail_write: Some("modelpublish-plugin-1.2.0".to_string()),
+            ..FailingSink::default()
+        };
+        let err = write_pins(&mut sink, &pins()).unwrap_err();
+        match &err {
+            CommitError::Write {
+                pin,
+                error,
+                rollback,
+            } => {
+                assert_eq!(**pin, pins()[1]);
+                assert_eq!(error.to_string(), "disk full");
+                assert!(rollback.is_none());
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert_eq!(
+            err.pin_errors(),
+            vec![(pins()[1].clone(), "disk full".to_string())]
+        );
+        assert!(sink.rolled_back);
+        assert!(sink.sink.pins().is_empty());
+        // the sink is free to begin another transaction
+        assert!(write_pins(&mut sink.sink, &pins()[..1]).is_ok());
+    }
+
+    #[test]
+    fn rolls_back_when_the_commit_fails() {
+        let mut sink = FailingSink {
+            fail_commit: true,
+            ..FailingSink::default()
+        };
+        let err = write_pins(&mut sink, &pins()).unwrap_err();
+        match &err {
+            CommitError::Commit { error, rollback } => {
+                assert_eq!(error.to_string(), "database is locked");
+                assert!(rollback.is_none());
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(err.pin_errors().is_empty());
+        assert!(sink.rolled_back);
+        assert!(sink.sink.pins().is_empty());
+    }
+
+    #[test]
+    fn records_a_failed_rollback() {
+        let mut sink = FailingSink {
+            fail_commit: true,
+            fail_rollback: true,
+            ..FailingSink::default()
+        };
+        match write_pins(&mut sink, &pins()).unwrap_err() {
+            CommitError::Commit {
+                rollback: Some(rollback),
+                ..
+            } => assert_eq!(rollback.to_string(), "connection lost"),
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reports_a_transaction_which_cannot_begin() {
+        let mut sink = MemorySink::new();
+        sink.begin().unwrap();
+        match write_pins(&mut sink, &pins()).unwrap_err() {
+            CommitError::Begin(_) => (),
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+}
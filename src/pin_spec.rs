@@ -0,0 +1,115 @@
+//! A PinSpec describes a single version pin, as produced by expanding a VpinSelection.
+use crate::distribution::package_name;
+use crate::i18n::tr;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// PinSpec is a single version pin for a distribution at a level, for a role, site
+/// and platform.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinSpec {
+    /// The distribution being pinned
+    pub distribution: String,
+    /// The level the pin applies to (`show`, `show.seq` or `show.seq.shot`)
+    pub level: String,
+    /// Some specific role, or None if the pin applies to any role
+    pub role: Option<String>,
+    /// Some specific site, or None if the pin applies to any site
+    pub site: Option<String>,
+    /// Some specific platform (eg `linux-el9`), or None if the pin applies to any
+    /// platform
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    /// Some comment explaining why the pin is needed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Some ticket tracking the pin (eg `PROJ-123`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket: Option<String>,
+    /// Some date (`YYYY-MM-DD`) from which the pin takes effect, or None if it takes
+    /// effect immediately
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_from: Option<String>,
+    /// Some date (`YYYY-MM-DD`) on which the pin expires, or None if it never expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_on: Option<String>,
+}
+
+impl PinSpec {
+    /// Retrieve the show the pin belongs to: the first component of the level
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * The show name as a &str
+    pub fn show(&self) -> &str {
+        self.level.split('.').next().unwrap_or("")
+    }
+
+    /// Determine whether this pin applies everywhere the other pin does. A level
+    /// covers itself and every level beneath it (`show` covers `show.seq`, which
+    /// covers `show.seq.shot`), and a pin for any role, site or platform covers a pin
+    /// for a specific one. Pins for any version of the same package may cover one
+    /// another, but pins for different packages never do.
+    ///
+    /// # Arguments
+    /// * `other` - The PinSpec to compare against
+    ///
+    /// # Returns
+    /// * true if this pin covers the other
+    pub fn covers(&self, other: &PinSpec) -> bool {
+        package_name(self.distribution.as_str()) == package_name(other.distribution.as_str())
+            && level_covers(self.level.as_str(), other.level.as_str())
+            && (self.role.is_none() || self.role == other.role)
+            && (self.site.is_none() || self.site == other.site)
+            && (self.platform.is_none() || self.platform == other.platform)
+    }
+
+    /// Determine whether this pin pins the same distribution as the other pin, at the
+    /// same level and for the same role, site and platform. Pins differing only in
+    /// their comment, ticket or dates have the same target.
+    ///
+    /// # Arguments
+    /// * `other` - The PinSpec to compare against
+    ///
+    /// # Returns
+    /// * true if both pins have the same target
+    pub fn same_target(&self, other: &PinSpec) -> bool {
+        self.distribution == other.distribution
+            && self.level == other.level
+            && self.role == other.role
+            && self.site == other.site
+            && self.platform == other.platform
+    }
+}
+
+impl fmt::Display for PinSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let role = match &self.role {
+            Some(role) => role.clone(),
+            None => tr("any role"),
+        };
+        let site = match &self.site {
+            Some(site) => site.clone(),
+            None => tr("any site"),
+        };
+        match &self.platform {
+            Some(platform) => write!(
+                f,
+                "{} @ {} ({}, {}, {})",
+                self.distribution, self.level, role, site, platform
+            ),
+            None => write!(
+                f,
+                "{} @ {} ({}, {})",
+                self.distribution, self.level, role, site
+            ),
+        }
+    }
+}
+
+// Determine whether the level is the other level, or one of its parents
+pub(crate) fn level_covers(level: &str, other: &str) -> bool {
+    other == level || (other.starts_with(level) && other[level.len()..].starts_with('.'))
+}
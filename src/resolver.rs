@@ -0,0 +1,304 @@
+//! Resolution of the pin in effect for a package at a level, for a role, site and
+//! platform. Of the pins which apply, the most specific wins: a shot pin beats a
+//! sequence pin, which beats a show pin. At the same level, a pin for a specific role
+//! beats one for any role, then a pin for a specific site beats one for any site, and
+//! then a pin for a specific platform beats one for any platform.
+use crate::distribution::{package_name, version_change, VersionChange};
+use crate::pin_spec::{level_covers, PinSpec};
+
+/// ResolveQuery identifies the package, level, role, site and platform being resolved
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolveQuery {
+    /// The package name (eg `modelpublish`)
+    pub package: String,
+    /// The name of the show
+    pub show: String,
+    /// Some sequence, or None to resolve at the show
+    pub seq: Option<String>,
+    /// Some shot, or None to resolve at the sequence. Ignored without a sequence.
+    pub shot: Option<String>,
+    /// Some role, or None to consider only the pins which apply to any role
+    pub role: Option<String>,
+    /// Some site, or None to consider only the pins which apply to any site
+    pub site: Option<String>,
+    /// Some platform, or None to consider only the pins which apply to any platform
+    pub platform: Option<String>,
+}
+
+impl ResolveQuery {
+    /// Build the query for the scope of a pin: its package, level, role, site and
+    /// platform
+    ///
+    /// # Arguments
+    /// * `pin` - The PinSpec
+    ///
+    /// # Returns
+    /// * ResolveQuery instance
+    pub fn for_pin(pin: &PinSpec) -> ResolveQuery {
+        let mut pieces = pin.level.splitn(3, '.').map(String::from);
+        ResolveQuery {
+            package: package_name(pin.distribution.as_str()).to_string(),
+            show: pieces.next().unwrap_or_default(),
+            seq: pieces.next(),
+            shot: pieces.next(),
+            role: pin.role.clone(),
+            site: pin.site.clone(),
+            platform: pin.platform.clone(),
+        }
+    }
+
+    /// Build the level being resolved (`show`, `show.seq` or `show.seq.shot`)
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * The level as a String
+    pub fn level(&self) -> String {
+        match (&self.seq, &self.shot) {
+            (Some(seq), Some(shot)) => format!("{}.{}.{}", self.show, seq, shot),
+            (Some(seq), None) => format!("{}.{}", self.show, seq),
+            _ => self.show.clone(),
+        }
+    }
+}
+
+/// Find the pin in effect for the query
+///
+/// # Arguments
+/// * `pins` - The pins in place
+/// * `query` - The ResolveQuery
+///
+/// # Returns
+/// * Some winning PinSpec. When equally specific pins apply, the last one listed wins.
+/// * None if no pin applies
+pub fn resolve<'a>(pins: &'a [PinSpec], query: &ResolveQuery) -> Option<&'a PinSpec> {
+    let level = query.level();
+    pins.iter()
+        .filter(|pin| package_name(pin.distribution.as_str()) == query.package)
+        .filter(|pin| level_covers(pin.level.as_str(), level.as_str()))
+        .filter(|pin| pin.role.is_none() || pin.role == query.role)
+        .filter(|pin| pin.site.is_none() || pin.site == query.site)
+        .filter(|pin| pin.platform.is_none() || pin.platform == query.platform)
+        .max_by_key(|pin| specificity(pin))
+}
+
+// Rank a pin by level depth, then role, then site, then platform
+fn specificity(pin: &PinSpec) -> (usize, bool, bool, bool) {
+    (
+        pin.level.split('.').count(),
+        pin.role.is_some(),
+        pin.site.is_some(),
+        pin.platform.is_some(),
+    )
+}
+
+/// PinChange pairs a new pin with the pin currently in effect in its scope, and
+/// describes how the version changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinChange {
+    /// The new pin
+    pub pin: PinSpec,
+    /// The pin currently in effect in the new pin's scope
+    pub current: PinSpec,
+    /// How the version changes
+    pub change: VersionChange,
+}
+
+/// Compare each new pin against the pin currently in effect in its scope
+///
+/// # Arguments
+/// * `pins` - The new pins
+/// * `existing` - The pins in place
+///
+/// # Returns
+/// * Vector of PinChange, for each new pin whose scope already resolves to a
+///   comparable version
+pub fn version_changes(pins: &[PinSpec], existing: &[PinSpec]) -> Vec<PinChange> {
+    pins.iter()
+        .filter_map(|pin| {
+            let current = resolve(existing, &ResolveQuery::for_pin(pin))?;
+            let change = version_change(pin.distribution.as_str(), current.distribution.as_str())?;
+            Some(PinChange {
+                pin: pin.clone(),
+                current: current.clone(),
+                change,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pin(distribution: &str, level: &str, role: Option<&str>, site: Option<&str>) -> PinSpec {
+        PinSpec {
+            distribution: distribution.to_string(),
+            level: level.to_string(),
+            role: role.map(String::from),
+            site: site.map(String::from),
+            platform: None,
+            comment: None,
+            ticket: None,
+            effective_from: None,
+            expires_on: None,
+        }
+    }
+
+    fn on_platform(pin: PinSpec, platform: &str) -> PinSpec {
+        PinSpec {
+            platform: Some(platform.to_string()),
+            ..pin
+        }
+    }
+
+    fn query(level: &str) -> ResolveQuery {
+        ResolveQuery::for_pin(&pin("modelpublish-0.0.0", level, None, None))
+    }
+
+    fn resolved<'a>(pins: &'a [PinSpec], query: &ResolveQuery) -> Option<&'a str> {
+        resolve(pins, query).map(|pin| pin.distribution.as_str())
+    }
+
+    #[test]
+    fn splits_the_level_of_a_pin_into_the_query() {
+        let query = ResolveQuery::for_pin(&pin(
+            "modelpublish-1.2.0",
+            "DEV01.RD.0001",
+            Some("anim"),
+            None,
+        ));
+        assert_eq!(query.package, "modelpublish");
+        assert_eq!(query.show, "DEV01");
+        assert_eq!(query.seq.as_deref(), Some("RD"));
+        assert_eq!(query.shot.as_deref(), Some("0001"));
+        assert_eq!(query.role.as_deref(), Some("anim"));
+        assert_eq!(query.level(), "DEV01.RD.0001");
+    }
+
+    #[test]
+    fn prefers_shot_over_sequence_over_show() {
+        let pins = vec![
+            pin("modelpublish-1.2.0", "DEV01.RD.0001", None, None),
+            pin("modelpublish-1.1.0", "DEV01.RD", None, None),
+            pin("modelpublish-1.0.0", "DEV01", None, None),
+        ];
+        assert_eq!(
+            resolved(&pins, &query("DEV01.RD.0001")),
+            Some("modelpublish-1.2.0")
+        );
+        assert_eq!(
+            resolved(&pins, &query("DEV01.RD.0002")),
+            Some("modelpublish-1.1.0")
+        );
+        assert_eq!(
+            resolved(&pins, &query("DEV01.RD")),
+            Some("modelpublish-1.1.0")
+        );
+        assert_eq!(
+            resolved(&pins, &query("DEV01.AA")),
+            Some("modelpublish-1.0.0")
+        );
+        assert_eq!(resolved(&pins, &query("DEV01")), Some("modelpublish-1.0.0"));
+    }
+
+    #[test]
+    fn narrows_by_role_then_site_then_platform() {
+        let pins = vec![
+            on_platform(pin("modelpublish-1.4.0", "DEV01", None, None), "linux-el9"),
+            pin("modelpublish-1.3.0", "DEV01", None, Some("montreal")),
+            pin("modelpublish-1.2.0", "DEV01", Some("anim"), None),
+            pin("modelpublish-1.0.0", "DEV01", None, None),
+        ];
+        let mut query = query("DEV01");
+        assert_eq!(resolved(&pins, &query), Some("modelpublish-1.0.0"));
+        query.platform = Some("linux-el9".to_string());
+        assert_eq!(resolved(&pins, &query), Some("modelpublish-1.4.0"));
+        query.site = Some("montreal".to_string());
+        assert_eq!(resolved(&pins, &query), Some("modelpublish-1.3.0"));
+        query.role = Some("anim".to_string());
+        assert_eq!(resolved(&pins, &query), Some("modelpublish-1.2.0"));
+        // pins for another role, site or platform never apply
+        query.role = Some("fx".to_string());
+        query.site = Some("vancouver".to_string());
+        query.platform = Some("linux-el7".to_string());
+        assert_eq!(resolved(&pins, &query), Some("modelpublish-1.0.0"));
+    }
+
+    #[test]
+    fn prefers_a_deeper_level_over_a_narrower_scope() {
+        let pins = vec![
+            pin(
+                "modelpublish-1.2.0",
+                "DEV01",
+                Some("anim"),
+                Some("montreal"),
+            ),
+            pin("modelpublish-1.1.0", "DEV01.RD", None, None),
+        ];
+        let mut query = query("DEV01.RD");
+        query.role = Some("anim".to_string());
+        query.site = Some("montreal".to_string());
+        assert_eq!(resolved(&pins, &query), Some("modelpublish-1.1.0"));
+    }
+
+    #[test]
+    fn breaks_ties_in_favour_of_the_last_pin() {
+        let pins = vec![
+            pin("modelpublish-1.0.0", "DEV01.RD", None, None),
+            pin("modelpublish-1.1.0", "DEV01.RD", None, None),
+        ];
+        assert_eq!(
+            resolved(&pins, &query("DEV01.RD")),
+            Some("modelpublish-1.1.0")
+        );
+    }
+
+    #[test]
+    fn finds_no_pin_for_another_package_or_show() {
+        let pins = vec![
+            pin("texturepublish-1.0.0", "DEV01", None, None),
+            pin("modelpublish-1.0.0", "DEV02", None, None),
+            pin("modelpublish-1.1.0", "DEV01.RD", None, None),
+        ];
+        assert_eq!(resolved(&pins, &query("DEV01")), None);
+        assert_eq!(resolved(&pins, &query("DEV01.RDX")), None);
+        assert_eq!(resolved(&[], &query("DEV01")), None);
+    }
+
+    #[test]
+    fn compares_new_pins_against_the_pins_in_effect() {
+        let existing = vec![
+            pin("modelpublish-1.10.0", "DEV01", None, None),
+            pin("texturepublish-2.0.0", "DEV01", None, None),
+        ];
+        let pins = vec![
+            pin("modelpublish-1.9.0", "DEV01.RD", None, None),
+            pin("modelpublish-1.11.0", "DEV01", Some("anim"), None),
+            pin("modelpublish-1.10.0", "DEV01.RD.0001", None, None),
+            pin("lookdevpublish-1.0.0", "DEV01", None, None),
+        ];
+        let changes = version_changes(&pins, &existing);
+        assert_eq!(
+            changes,
+            vec![
+                PinChange {
+                    pin: pins[0].clone(),
+                    current: existing[0].clone(),
+                    change: VersionChange::Downgrade,
+                },
+                PinChange {
+                    pin: pins[1].clone(),
+                    current: existing[0].clone(),
+                    change: VersionChange::Upgrade,
+                },
+                PinChange {
+                    pin: pins[2].clone(),
+                    current: existing[0].clone(),
+                    change: VersionChange::NoOp,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! Keyboard shortcuts for driving the dialog without the mouse. The ShortcutMap
+//! associates each ShortcutAction with a key sequence in the portable text format
+//! understood by QKeySequence (eg `Ctrl+F`).
+use crate::i18n::tr;
+
+/// ShortcutAction enumerates the operations which may be bound to a shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShortcutAction {
+    /// Toggle the "Specify Roles" checkbox
+    ToggleRoles,
+    /// Toggle the "Specify Seq(s) / Shot(s)" checkbox
+    ToggleLevels,
+    /// Enable the roles and move focus to the roles filter
+    FocusRolesFilter,
+    /// Select every role
+    SelectAllRoles,
+    /// Deselect every role
+    SelectNoRoles,
+    /// Accept the dialog, as if OK had been pressed. By default this is bound to
+    /// `Ctrl+Return`, the main Enter key. A ShortcutMap binds one key sequence per
+    /// action, so the keypad's Enter (`Ctrl+Enter` in QKeySequence's format) is
+    /// not bound as well; it may be bound in place of `Ctrl+Return` via `set`.
+    Accept,
+    /// Fetch the dialog's data again, as if Refresh had been pressed
+    Refresh,
+}
+
+impl ShortcutAction {
+    /// Every action, in the order in which it is listed in the help
+    pub const ALL: [ShortcutAction; 7] = [
+        ShortcutAction::ToggleRoles,
+        ShortcutAction::ToggleLevels,
+        ShortcutAction::FocusRolesFilter,
+        ShortcutAction::SelectAllRoles,
+        ShortcutAction::SelectNoRoles,
+        ShortcutAction::Accept,
+        ShortcutAction::Refresh,
+    ];
+
+    /// Retrieve a description of the action suitable for display. The description
+    /// is in English; `help_text` translates it.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * The description as a &str
+    pub fn description(&self) -> &'static str {
+        match self {
+            ShortcutAction::ToggleRoles => "Toggle Specify Roles",
+            ShortcutAction::ToggleLevels => "Toggle Specify Seq(s) / Shot(s)",
+            ShortcutAction::FocusRolesFilter => "Filter roles",
+            ShortcutAction::SelectAllRoles => "Select all roles",
+            ShortcutAction::SelectNoRoles => "Select no roles",
+            ShortcutAction::Accept => "Accept",
+            ShortcutAction::Refresh => "Refresh",
+        }
+    }
+}
+
+/// ShortcutMap binds ShortcutActions to key sequences. Actions without a key
+/// sequence are not bound.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortcutMap {
+    bindings: Vec<(ShortcutAction, String)>,
+}
+
+impl Default for ShortcutMap {
+    fn default() -> Self {
+        let mut map = ShortcutMap::empty();
+        map.set(ShortcutAction::ToggleRoles, "Alt+R");
+        map.set(ShortcutAction::ToggleLevels, "Alt+S");
+        map.set(ShortcutAction::FocusRolesFilter, "Ctrl+F");
+        map.set(ShortcutAction::SelectAllRoles, "Ctrl+Shift+A");
+        map.set(ShortcutAction::SelectNoRoles, "Ctrl+Shift+D");
+        map.set(ShortcutAction::Accept, "Ctrl+Return");
+        map.set(ShortcutAction::Refresh, "F5");
+        map
+    }
+}
+
+impl ShortcutMap {
+    /// Create a ShortcutMap without any bindings
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * ShortcutMap instance
+    pub fn empty() -> Self {
+        ShortcutMap {
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Bind the action to the supplied key sequence, replacing any existing binding
+    ///
+    /// # Arguments
+    /// * `action` - The ShortcutAction to bind
+    /// * `keys` - The key sequence, in portable text format (eg `Ctrl+F`)
+    ///
+    /// # Returns
+    /// * None
+    pub fn set<I: Into<String>>(&mut self, action: ShortcutAction, keys: I) {
+        let keys = keys.into();
+        match self.bindings.iter_mut().find(|(act, _)| *act == action) {
+            Some(binding) => binding.1 = keys,
+            None => self.bindings.push((action, keys)),
+        }
+    }
+
+    /// Remove the binding for the supplied action
+    ///
+    /// # Arguments
+    /// * `action` - The ShortcutAction to unbind
+    ///
+    /// # Returns
+    /// * None
+    pub fn unset(&mut self, action: ShortcutAction) {
+        self.bindings.retain(|(act, _)| *act != action);
+    }
+
+    /// Retrieve the key sequence bound to the supplied action
+    ///
+    /// # Arguments
+    /// * `action` - The ShortcutAction
+    ///
+    /// # Returns
+    /// * Some key sequence if the action is bound
+    /// * None otherwise
+    pub fn get(&self, action: ShortcutAction) -> Option<&str> {
+        self.bindings
+            .iter()
+            .find(|(act, _)| *act == action)
+            .map(|(_, keys)| keys.as_str())
+    }
+
+    /// Iterate over the bindings, in the order in which they are listed in the help
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Iterator over (ShortcutAction, key sequence) tuples
+    pub fn iter(&self) -> impl Iterator<Item = (ShortcutAction, &str)> {
+        ShortcutAction::ALL
+            .iter()
+            .filter_map(move |action| self.get(*action).map(|keys| (*action, keys)))
+    }
+
+    /// Generate the help text listing each bound shortcut on its own line, with the
+    /// descriptions translated
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * The help text as a String
+    pub fn help_text(&self) -> String {
+        self.iter()
+            .map(|(action, keys)| format!("{}: {}", keys, tr(action.description())))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binds_every_action_by_default() {
+        let shortcuts = ShortcutMap::default();
+        for action in ShortcutAction::ALL.iter() {
+            assert!(shortcuts.get(*action).is_some(), "{:?} is unbound", action);
+        }
+        assert_eq!(shortcuts.iter().count(), ShortcutAction::ALL.len());
+        assert_eq!(shortcuts.get(ShortcutAction::Accept), Some("Ctrl+Return"));
+        assert_eq!(shortcuts.get(ShortcutAction::Refresh), Some("F5"));
+    }
+
+    #[test]
+    fn replaces_an_existing_binding() {
+        let mut shortcuts = ShortcutMap::default();
+        shortcuts.set(ShortcutAction::Accept, "Ctrl+Enter");
+        assert_eq!(shortcuts.get(ShortcutAction::Accept), Some("Ctrl+Enter"));
+        assert_eq!(shortcuts.iter().count(), ShortcutAction::ALL.len());
+    }
+
+    #[test]
+    fn unbinds_an_action() {
+        let mut shortcuts = ShortcutMap::default();
+        shortcuts.unset(ShortcutAction::FocusRolesFilter);
+        assert_eq!(shortcuts.get(ShortcutAction::FocusRolesFilter), None);
+        assert!(shortcuts
+            .iter()
+            .all(|(action, _)| action != ShortcutAction::FocusRolesFilter));
+        // unbinding an unbound action has no effect
+        shortcuts.unset(ShortcutAction::FocusRolesFilter);
+        assert_eq!(shortcuts.iter().count(), ShortcutAction::ALL.len() - 1);
+    }
+
+    #[test]
+    fn looks_up_unbound_actions() {
+        let mut shortcuts = ShortcutMap::empty();
+        assert_eq!(shortcuts.get(ShortcutAction::ToggleRoles), None);
+        assert_eq!(shortcuts.iter().count(), 0);
+        assert_eq!(shortcuts.help_text(), "");
+        shortcuts.set(ShortcutAction::ToggleLevels, "Alt+L");
+        assert_eq!(shortcuts.get(ShortcutAction::ToggleRoles), None);
+    }
+
+    #[test]
+    fn lists_the_help_in_action_order() {
+        let mut shortcuts = ShortcutMap::empty();
+        shortcuts.set(ShortcutAction::Refresh, "F5");
+        shortcuts.set(ShortcutAction::Accept, "Ctrl+Return");
+        shortcuts.set(ShortcutAction::ToggleRoles, "Alt+R");
+        assert_eq!(
+            shortcuts.help_text(),
+            format!(
+                "Alt+R: {}\nCtrl+Return: {}\nF5: {}",
+                tr("Toggle Specify Roles"),
+                tr("Accept"),
+                tr("Refresh")
+            )
+        );
+        let lines = ShortcutMap::default()
+            .help_text()
+            .lines()
+            .map(|line| line.split(':').next().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "Alt+R",
+                "Alt+S",
+                "Ctrl+F",
+                "Ctrl+Shift+A",
+                "Ctrl+Shift+D",
+                "Ctrl+Return",
+                "F5"
+            ]
+        );
+    }
+}
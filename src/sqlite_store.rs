@@ -0,0 +1,456 @@
+//! A pin store backed by an SQLite file, for working offline and for exercising
+//! the commit path without the production database. It implements both PinSink
+//! and PinProvider, so the dialog may read and write pins locally.
+//!
+//! The schema is versioned via SQLite's `user_version` pragma. Opening a store
+//! applies any migrations the file has not yet seen.
+use crate::distribution::package_name;
+use crate::pin_provider::PinProvider;
+use crate::pin_sink::{PinSink, SinkError};
+use crate::pin_spec::PinSpec;
+use rusqlite::types::ToSql;
+use rusqlite::{params, Connection, Row, NO_PARAMS};
+use serde::Serialize;
+use std::path::Path;
+
+pub use rusqlite::Error as SqliteError;
+
+// Each migration takes the schema from the version matching its index to the next
+const MIGRATIONS: &[&str] = &[
+    // version 1: the pins table
+    "CREATE TABLE pins (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        distribution TEXT NOT NULL,
+        package TEXT NOT NULL,
+        show TEXT NOT NULL,
+        level TEXT NOT NULL,
+        role TEXT,
+        site TEXT,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE INDEX pins_show ON pins (show);
+    CREATE INDEX pins_package ON pins (package);",
+    // version 2: the comment and ticket recorded with each pin
+    "ALTER TABLE pins ADD COLUMN comment TEXT;
+    ALTER TABLE pins ADD COLUMN ticket TEXT;",
+    // version 3: the dates between which each pin applies
+    "ALTER TABLE pins ADD COLUMN effective_from TEXT;
+    ALTER TABLE pins ADD COLUMN expires_on TEXT;",
+    // version 4: the platform each pin applies to
+    "ALTER TABLE pins ADD COLUMN platform TEXT;",
+];
+
+/// PinQuery filters the pins in the store. Each field which is Some restricts the
+/// pins to those with a matching value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PinQuery {
+    /// Some show
+    pub show: Option<String>,
+    /// Some level (`show`, `show.seq` or `show.seq.shot`)
+    pub level: Option<String>,
+    /// Some role. Pins for any role do not match a specific role.
+    pub role: Option<String>,
+    /// Some site. Pins for any site do not match a specific site.
+    pub site: Option<String>,
+    /// Some platform. Pins for any platform do not match a specific platform.
+    pub platform: Option<String>,
+}
+
+/// StoredPin is a PinSpec along with its identifier in the store
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StoredPin {
+    /// The identifier of the pin, used to delete it
+    pub id: i64,
+    /// The pin
+    #[serde(flatten)]
+    pub pin: PinSpec,
+}
+
+/// SqliteStore reads and writes pins in an SQLite file
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    /// Open the store at the supplied path, creating it if need be, and migrate
+    /// the schema to the current version
+    ///
+    /// # Arguments
+    /// * `path` - The path to the SQLite file
+    ///
+    /// # Returns
+    /// * SqliteStore instance if the file could be opened and migrated
+    /// * SqliteError otherwise
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SqliteError> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Open a store held in memory, which is discarded when dropped
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * SqliteStore instance
+    /// * SqliteError if the schema could not be created
+    pub fn open_in_memory() -> Result<Self, SqliteError> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    /// Retrieve the version of the schema
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * The schema version
+    /// * SqliteError if it could not be read
+    pub fn schema_version(&self) -> Result<usize, SqliteError> {
+        let version: i64 = self
+            .conn
+            .query_row("PRAGMA user_version", NO_PARAMS, |row| row.get(0))?;
+        Ok(version as usize)
+    }
+
+    /// Retrieve the pins matching the query, in the order in which they were written
+    ///
+    /// # Arguments
+    /// * `query` - The PinQuery
+    ///
+    /// # Returns
+    /// * Vector of StoredPin
+    /// * SqliteError if the query failed
+    pub fn query(&self, query: &PinQuery) -> Result<Vec<StoredPin>, SqliteError> {
+        let mut sql = String::from(
+            "SELECT id, distribution, level, role, site, comment, ticket, effective_from, \
+             expires_on, platform FROM pins",
+        );
+        let mut clauses = Vec::new();
+        let mut values: Vec<&dyn ToSql> = Vec::new();
+        for (column, value) in &[
+            ("show", &query.show),
+            ("level", &query.level),
+            ("role", &query.role),
+            ("site", &query.site),
+            ("platform", &query.platform),
+        ] {
+            if let Some(value) = value {
+                clauses.push(format!("{} = ?", column));
+                values.push(value);
+            }
+        }
+        if !clauses.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(clauses.join(" AND ").as_str());
+        }
+        sql.push_str(" ORDER BY id");
+        let mut stmt = self.conn.prepare(sql.as_str())?;
+        let rows = stmt.query_map(values, Self::stored_pin_from_row)?;
+        rows.collect()
+    }
+
+    /// Delete the pin with the supplied identifier
+    ///
+    /// # Arguments
+    /// * `id` - The identifier of the pin
+    ///
+    /// # Returns
+    /// * true if a pin was deleted, false if there is no such pin
+    /// * SqliteError if the deletion failed
+    pub fn delete(&self, id: i64) -> Result<bool, SqliteError> {
+        let deleted = self
+            .conn
+            .execute("DELETE FROM pins WHERE id = ?", params![id])?;
+        Ok(deleted > 0)
+    }
+
+    // wrap the connection, migrating the schema
+    fn from_connection(conn: Connection) -> Result<Self, SqliteError> {
+        let mut store = SqliteStore { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    // apply the migrations the schema has not yet seen, each within a transaction
+    // which is rolled back, when dropped, should the migration fail
+    fn migrate(&mut self) -> Result<(), SqliteError> {
+        let version = self.schema_version()?;
+        for (idx, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = self.conn.transaction()?;
+            tx.execute_batch(migration)?;
+            tx.execute_batch(format!("PRAGMA user_version = {}", idx + 1).as_str())?;
+            tx.commit()?;
+        }
+        Ok(())
+    }
+
+    // build a StoredPin from a row selected by `query`
+    fn stored_pin_from_row(row: &Row) -> Result<StoredPin, SqliteError> {
+        Ok(StoredPin {
+            id: row.get(0)?,
+            pin: PinSpec {
+                distribution: row.get(1)?,
+                level: row.get(2)?,
+                role: row.get(3)?,
+                site: row.get(4)?,
+                comment: row.get(5)?,
+                ticket: row.get(6)?,
+                effective_from: row.get(7)?,
+                expires_on: row.get(8)?,
+                platform: row.get(9)?,
+            },
+        })
+    }
+}
+
+impl PinSink for SqliteStore {
+    fn begin(&mut self) -> Result<(), SinkError> {
+        self.conn.execute_batch("BEGIN")?;
+        Ok(())
+    }
+
+    fn write(&mut self, pin: &PinSpec) -> Result<(), SinkError> {
+        self.conn.execute(
+            "INSERT INTO pins (distribution, package, show, level, role, site, comment, ticket,
+                               effective_from, expires_on, platform)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                pin.distribution,
+                package_name(pin.distribution.as_str()),
+                pin.show(),
+                pin.level,
+                pin.role,
+                pin.site,
+                pin.comment,
+                pin.ticket,
+                pin.effective_from,
+                pin.expires_on,
+                pin.platform
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), SinkError> {
+        self.conn.execute_batch("COMMIT")?;
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<(), SinkError> {
+        self.conn.execute_batch("ROLLBACK")?;
+        Ok(())
+    }
+}
+
+impl PinProvider for SqliteStore {
+    fn existing_pins(&mut self, show: &str) -> Result<Vec<PinSpec>, SinkError> {
+        let query = PinQuery {
+            show: Some(show.to_string()),
+            ..PinQuery::default()
+        };
+        Ok(self
+            .query(&query)?
+            .into_iter()
+            .map(|stored| stored.pin)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pin_sink::write_pins;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn pin(distribution: &str, level: &str, role: Option<&str>, site: Option<&str>) -> PinSpec {
+        PinSpec {
+            distribution: distribution.to_string(),
+            level: level.to_string(),
+            role: role.map(String::from),
+            site: site.map(String::from),
+            platform: None,
+            comment: None,
+            ticket: None,
+            effective_from: None,
+            expires_on: None,
+        }
+    }
+
+    // Retrieve a path in the temp directory named after the test, removing any
+    // file left behind by a previous run
+    fn db_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("pbgui-vpin-{}-{}.db", std::process::id(), name));
+        fs::remove_file(&path).ok();
+        path
+    }
+
+    fn store_with_pins() -> SqliteStore {
+        let mut store = SqliteStore::open_in_memory().unwrap();
+        write_pins(
+            &mut store,
+            &[
+                pin("modelpublish-1.0.0", "DEV01", None, None),
+                pin("modelpublish-1.1.0", "DEV01.RD", Some("anim"), None),
+                pin(
+                    "modelpublish-1.2.0",
+                    "DEV01.RD.0001",
+                    Some("anim"),
+                    Some("montreal"),
+                ),
+                pin("modelpublish-2.0.0", "DEV02", None, Some("montreal")),
+            ],
+        )
+        .unwrap();
+        store
+    }
+
+    fn distributions(pins: Vec<StoredPin>) -> Vec<String> {
+        pins.into_iter()
+            .map(|stored| stored.pin.distribution)
+            .collect()
+    }
+
+    #[test]
+    fn migrates_a_new_store_to_the_current_version() {
+        let mut store = SqliteStore::open_in_memory().unwrap();
+        assert_eq!(store.schema_version().unwrap(), MIGRATIONS.len());
+        let pin = PinSpec {
+            platform: Some("linux-el9".to_string()),
+            comment: Some("hotfix".to_string()),
+            ticket: Some("PROJ-123".to_string()),
+            effective_from: Some("2024-03-01".to_string()),
+            expires_on: Some("2024-04-01".to_string()),
+            ..pin(
+                "modelpublish-1.0.0",
+                "DEV01",
+                Some("anim"),
+                Some("montreal"),
+            )
+        };
+        write_pins(&mut store, std::slice::from_ref(&pin)).unwrap();
+        assert_eq!(store.existing_pins("DEV01").unwrap(), vec![pin]);
+    }
+
+    #[test]
+    fn upgrades_a_version_1_store() {
+        let path = db_path("upgrade");
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(MIGRATIONS[0]).unwrap();
+            conn.execute_batch(
+                "INSERT INTO pins (distribution, package, show, level, role, site)
+                 VALUES ('modelpublish-1.0.0', 'modelpublish', 'DEV01', 'DEV01', 'anim', NULL);
+                 PRAGMA user_version = 1;",
+            )
+            .unwrap();
+        }
+        let mut store = SqliteStore::open(&path).unwrap();
+        assert_eq!(store.schema_version().unwrap(), MIGRATIONS.len());
+        assert_eq!(
+            store.existing_pins("DEV01").unwrap(),
+            vec![pin("modelpublish-1.0.0", "DEV01", Some("anim"), None)]
+        );
+        drop(store);
+        // reopening a current store applies no migrations
+        let store = SqliteStore::open(&path).unwrap();
+        assert_eq!(store.schema_version().unwrap(), MIGRATIONS.len());
+        drop(store);
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn rolls_back_a_failed_migration() {
+        let path = db_path("failed-migration");
+        {
+            // a ticket column added by hand makes the version 2 migration fail part way
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(MIGRATIONS[0]).unwrap();
+            conn.execute_batch("ALTER TABLE pins ADD COLUMN ticket TEXT; PRAGMA user_version = 1;")
+                .unwrap();
+        }
+        assert!(SqliteStore::open(&path).is_err());
+        let conn = Connection::open(&path).unwrap();
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", NO_PARAMS, |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, 1);
+        // the comment column, added before the failure, was rolled back
+        assert!(conn.prepare("SELECT comment FROM pins").is_err());
+        drop(conn);
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn queries_by_show_level_role_and_site() {
+        let store = store_with_pins();
+        let query = |query: PinQuery| distributions(store.query(&query).unwrap());
+        assert_eq!(query(PinQuery::default()).len(), 4);
+        assert_eq!(
+            query(PinQuery {
+                show: Some("DEV01".to_string()),
+                ..PinQuery::default()
+            }),
+            vec![
+                "modelpublish-1.0.0",
+                "modelpublish-1.1.0",
+                "modelpublish-1.2.0"
+            ]
+        );
+        assert_eq!(
+            query(PinQuery {
+                level: Some("DEV01.RD".to_string()),
+                ..PinQuery::default()
+            }),
+            vec!["modelpublish-1.1.0"]
+        );
+        assert_eq!(
+            query(PinQuery {
+                role: Some("anim".to_string()),
+                ..PinQuery::default()
+            }),
+            vec!["modelpublish-1.1.0", "modelpublish-1.2.0"]
+        );
+        assert_eq!(
+            query(PinQuery {
+                show: Some("DEV01".to_string()),
+                site: Some("montreal".to_string()),
+                ..PinQuery::default()
+            }),
+            vec!["modelpublish-1.2.0"]
+        );
+        assert!(query(PinQuery {
+            show: Some("DEV03".to_string()),
+            ..PinQuery::default()
+        })
+        .is_empty());
+    }
+
+    #[test]
+    fn deletes_pins_by_id() {
+        let store = store_with_pins();
+        let id = store.query(&PinQuery::default()).unwrap()[1].id;
+        assert!(store.delete(id).unwrap());
+        assert!(!store.delete(id).unwrap());
+        assert_eq!(
+            distributions(store.query(&PinQuery::default()).unwrap()),
+            vec![
+                "modelpublish-1.0.0",
+                "modelpublish-1.2.0",
+                "modelpublish-2.0.0"
+            ]
+        );
+    }
+
+    #[test]
+    fn discards_the_pins_written_before_a_rollback() {
+        let mut store = store_with_pins();
+        store.begin().unwrap();
+        store
+            .write(&pin("modelpublish-3.0.0", "DEV01", None, None))
+            .unwrap();
+        store.rollback().unwrap();
+        assert_eq!(store.existing_pins("DEV01").unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,127 @@
+//! Helpers for loading stylesheets from disk, and for re-applying them when the
+//! file changes so that the look of the dialog may be iterated upon without
+//! recompiling.
+use qt_core::{QFileSystemWatcher, QString, SlotOfQString};
+use qt_widgets::{
+    cpp_core::{CastInto, CppBox, MutPtr, Ref},
+    QWidget,
+};
+use rustqt_utils::qs;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming a stylesheet which replaces the built in one
+pub const STYLESHEET_ENV_VAR: &str = "PBGUI_VPIN_STYLESHEET";
+/// Environment variable which, when set, watches the stylesheet named by
+/// `STYLESHEET_ENV_VAR` and re-applies it whenever it changes
+pub const WATCH_STYLESHEET_ENV_VAR: &str = "PBGUI_VPIN_WATCH_STYLESHEET";
+
+/// Retrieve the stylesheet path from the environment, if one has been set
+///
+/// # Arguments
+/// * None
+///
+/// # Returns
+/// * Some PathBuf if `PBGUI_VPIN_STYLESHEET` is set
+/// * None otherwise
+pub fn stylesheet_path_from_env() -> Option<PathBuf> {
+    std::env::var_os(STYLESHEET_ENV_VAR)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Determine whether the user has asked for the stylesheet to be watched
+///
+/// # Arguments
+/// * None
+///
+/// # Returns
+/// * true if `PBGUI_VPIN_WATCH_STYLESHEET` is set
+pub fn watch_stylesheet_from_env() -> bool {
+    std::env::var_os(WATCH_STYLESHEET_ENV_VAR).is_some()
+}
+
+/// Read the stylesheet at the supplied path and apply it to the widget
+///
+/// # Arguments
+/// * `path` - The path to the qss file
+/// * `widget` - The widget to style
+///
+/// # Returns
+/// * Ok if the file was read and applied
+/// * io::Error if the file could not be read
+pub unsafe fn apply_stylesheet_from_path(
+    path: &Path,
+    widget: impl CastInto<MutPtr<QWidget>>,
+) -> io::Result<()> {
+    let contents = fs::read_to_string(path)?;
+    let mut widget = widget.cast_into();
+    widget.set_style_sheet(&qs(contents.as_str()));
+    Ok(())
+}
+
+/// StylesheetWatcher re-applies a stylesheet to a widget whenever the file
+/// changes on disk. Failures to reload are reported to the supplied callback.
+/// Watching stops when the StylesheetWatcher is dropped.
+pub struct StylesheetWatcher<'a> {
+    watcher: CppBox<QFileSystemWatcher>,
+    file_changed: SlotOfQString<'a>,
+}
+
+impl<'a> StylesheetWatcher<'a> {
+    /// Create a new StylesheetWatcher
+    ///
+    /// # Arguments
+    /// * `path` - The path to the qss file
+    /// * `widget` - The widget to style
+    /// * `on_error` - Invoked with the path and the error whenever the stylesheet
+    ///   cannot be reloaded
+    ///
+    /// # Returns
+    /// * StylesheetWatcher instance
+    pub unsafe fn new(
+        path: &Path,
+        widget: impl CastInto<MutPtr<QWidget>>,
+        on_error: impl Fn(&Path, io::Error) + 'a,
+    ) -> StylesheetWatcher<'a> {
+        let widget: MutPtr<QWidget> = widget.cast_into();
+        let mut watcher = QFileSystemWatcher::new_0a();
+        let mut watcher_ptr = watcher.as_mut_ptr();
+        let path_str = path.to_string_lossy().to_string();
+        watcher.add_path(&qs(path_str.as_str()));
+        let file_changed = SlotOfQString::new(move |changed: Ref<QString>| {
+            let changed = PathBuf::from(changed.to_std_string());
+            if let Err(err) = apply_stylesheet_from_path(&changed, widget) {
+                on_error(&changed, err);
+            }
+            // editors frequently replace the file rather than writing to it, which
+            // removes it from the watcher. So we add it back in.
+            if !watcher_ptr
+                .files()
+                .contains_q_string(&qs(path_str.as_str()))
+            {
+                watcher_ptr.add_path(&qs(path_str.as_str()));
+            }
+        });
+        watcher.file_changed().connect(&file_changed);
+        StylesheetWatcher {
+            watcher,
+            file_changed,
+        }
+    }
+
+    /// Retrieve the paths being watched
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of watched paths
+    pub unsafe fn paths(&self) -> Vec<PathBuf> {
+        let files = self.watcher.files();
+        (0..files.length())
+            .map(|idx| PathBuf::from(files.at(idx).to_std_string()))
+            .collect()
+    }
+}
@@ -0,0 +1,155 @@
+//! Built in themes for the dialog. A theme pairs a stylesheet with a QPalette, so
+//! that the dialog looks consistent regardless of the palette of the host application.
+use qt_gui::{
+    q_palette::{ColorGroup, ColorRole},
+    QColor, QPalette,
+};
+use qt_widgets::{
+    cpp_core::{CastInto, CppBox, MutPtr},
+    QApplication, QWidget,
+};
+use rustqt_utils::qs;
+
+/// The stylesheet shared by every theme. It only sets margins and font sizes.
+pub(crate) const STYLE_STR: &str = include_str!("../resources/pbgui_vpin.qss");
+const LIGHT_STYLE_STR: &str = include_str!("../resources/pbgui_vpin_light.qss");
+const DARK_STYLE_STR: &str = include_str!("../resources/pbgui_vpin_dark.qss");
+
+type Rgb = (i32, i32, i32);
+
+const LIGHT_COLORS: &[(ColorRole, Rgb)] = &[
+    (ColorRole::Window, (244, 244, 244)),
+    (ColorRole::WindowText, (32, 32, 32)),
+    (ColorRole::Base, (255, 255, 255)),
+    (ColorRole::AlternateBase, (236, 236, 236)),
+    (ColorRole::ToolTipBase, (255, 255, 220)),
+    (ColorRole::ToolTipText, (32, 32, 32)),
+    (ColorRole::Text, (32, 32, 32)),
+    (ColorRole::Button, (230, 230, 230)),
+    (ColorRole::ButtonText, (32, 32, 32)),
+    (ColorRole::BrightText, (179, 38, 30)),
+    (ColorRole::Highlight, (61, 143, 209)),
+    (ColorRole::HighlightedText, (255, 255, 255)),
+    (ColorRole::Link, (29, 95, 168)),
+];
+
+const DARK_COLORS: &[(ColorRole, Rgb)] = &[
+    (ColorRole::Window, (43, 43, 43)),
+    (ColorRole::WindowText, (220, 220, 220)),
+    (ColorRole::Base, (30, 30, 30)),
+    (ColorRole::AlternateBase, (51, 51, 51)),
+    (ColorRole::ToolTipBase, (60, 60, 60)),
+    (ColorRole::ToolTipText, (220, 220, 220)),
+    (ColorRole::Text, (220, 220, 220)),
+    (ColorRole::Button, (53, 53, 53)),
+    (ColorRole::ButtonText, (220, 220, 220)),
+    (ColorRole::BrightText, (242, 139, 130)),
+    (ColorRole::Highlight, (47, 110, 165)),
+    (ColorRole::HighlightedText, (255, 255, 255)),
+    (ColorRole::Link, (108, 180, 238)),
+];
+
+// Colors applied to the Disabled group, so that disabled controls are distinguishable
+const LIGHT_DISABLED_COLORS: &[(ColorRole, Rgb)] = &[
+    (ColorRole::WindowText, (160, 160, 160)),
+    (ColorRole::Text, (160, 160, 160)),
+    (ColorRole::ButtonText, (160, 160, 160)),
+];
+
+const DARK_DISABLED_COLORS: &[(ColorRole, Rgb)] = &[
+    (ColorRole::WindowText, (110, 110, 110)),
+    (ColorRole::Text, (110, 110, 110)),
+    (ColorRole::ButtonText, (110, 110, 110)),
+];
+
+/// Theme selects the look of the dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    /// The default stylesheet, along with the palette of the host application
+    #[default]
+    Host,
+    /// The bundled light theme
+    Light,
+    /// The bundled dark theme
+    Dark,
+    /// The default stylesheet, along with the palette of the parent widget, falling
+    /// back on the palette of the host application when there is no parent
+    Inherit,
+}
+
+impl Theme {
+    /// Retrieve the full stylesheet for the theme
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * The stylesheet as a String
+    pub fn stylesheet(&self) -> String {
+        match self {
+            Theme::Host | Theme::Inherit => STYLE_STR.to_string(),
+            Theme::Light => format!("{}{}", STYLE_STR, LIGHT_STYLE_STR),
+            Theme::Dark => format!("{}{}", STYLE_STR, DARK_STYLE_STR),
+        }
+    }
+
+    /// Build the palette for the theme
+    ///
+    /// # Arguments
+    /// * `widget` - The widget being themed, used to look up the parent for `Theme::Inherit`
+    ///
+    /// # Returns
+    /// * The QPalette for the theme
+    pub unsafe fn palette(&self, widget: MutPtr<QWidget>) -> CppBox<QPalette> {
+        match self {
+            Theme::Host => QApplication::palette_0a(),
+            Theme::Inherit => {
+                let parent = if widget.is_null() {
+                    widget
+                } else {
+                    widget.parent_widget()
+                };
+                if parent.is_null() {
+                    QApplication::palette_0a()
+                } else {
+                    QPalette::new_copy(parent.palette())
+                }
+            }
+            Theme::Light => Self::build_palette(LIGHT_COLORS, LIGHT_DISABLED_COLORS),
+            Theme::Dark => Self::build_palette(DARK_COLORS, DARK_DISABLED_COLORS),
+        }
+    }
+
+    /// Apply the theme's stylesheet and palette to the supplied widget
+    ///
+    /// # Arguments
+    /// * `widget` - The widget to theme
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn apply(&self, widget: impl CastInto<MutPtr<QWidget>>) {
+        let mut widget = widget.cast_into();
+        let palette = self.palette(widget);
+        widget.set_palette(&palette);
+        widget.set_style_sheet(&qs(self.stylesheet().as_str()));
+    }
+
+    // construct a palette from the supplied active and disabled colors
+    unsafe fn build_palette(
+        colors: &[(ColorRole, Rgb)],
+        disabled_colors: &[(ColorRole, Rgb)],
+    ) -> CppBox<QPalette> {
+        let mut palette = QPalette::new();
+        for (role, (r, g, b)) in colors {
+            palette.set_color_2a(*role, &QColor::from_rgb_3a(*r, *g, *b));
+        }
+        for (role, (r, g, b)) in disabled_colors {
+            palette.set_color_3a(
+                ColorGroup::Disabled,
+                *role,
+                &QColor::from_rgb_3a(*r, *g, *b),
+            );
+        }
+        palette
+    }
+}
@@ -0,0 +1,666 @@
+//! Validation of a VpinSelection against the roles, levels, sites and platforms on
+//! offer. The VpinDialog and the command line batch mode share this code path, so that
+//! a selection read from a file is held to the same rules as one made interactively.
+//!
+//! Warnings flag selections which are valid, but likely to be regretted.
+use crate::i18n::{tr, trf};
+use crate::vpin_selection::{LevelMap, VpinSelection};
+use regex::Regex;
+use std::error::Error;
+use std::fmt;
+
+/// SelectionError describes why a VpinSelection is invalid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectionError {
+    /// The selection has no show
+    MissingShow,
+    /// The selection has no distribution, or an empty one
+    MissingDistribution,
+    /// The distribution is not one of the distributions on offer
+    UnknownDistribution(String),
+    /// Specific roles were requested, but none were selected
+    NoRolesSelected,
+    /// The role is not one of the roles on offer
+    UnknownRole(String),
+    /// The site is not one of the sites on offer
+    UnknownSite(String),
+    /// Specific platforms were requested, but none were given
+    NoPlatformsSelected,
+    /// The platform is not one of the platforms on offer
+    UnknownPlatform(String),
+    /// The level is not of the form `show.seq` or `show.seq.shot` for the current show
+    InvalidLevel(String),
+    /// The sequence is not in the LevelMap
+    UnknownSequence(String),
+    /// The shot does not belong to the sequence in the LevelMap
+    UnknownShot { sequence: String, shot: String },
+    /// A comment is required, but none was given
+    MissingComment,
+    /// A ticket is required, but none was given
+    MissingTicket,
+    /// The ticket does not match the ticket pattern
+    InvalidTicket { ticket: String, pattern: String },
+    /// The ticket pattern is not a valid regular expression
+    InvalidTicketPattern { pattern: String, error: String },
+    /// The date is not a valid `YYYY-MM-DD` date
+    InvalidDate(String),
+    /// The pins would expire on or before the date they take effect
+    ExpiryNotAfterEffective {
+        effective_from: String,
+        expires_on: String,
+    },
+}
+
+impl fmt::Display for SelectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            SelectionError::MissingShow => tr("No show specified"),
+            SelectionError::MissingDistribution => tr("No distribution specified"),
+            SelectionError::UnknownDistribution(distribution) => {
+                trf("Unknown distribution: {}", &[distribution])
+            }
+            SelectionError::NoRolesSelected => {
+                tr("Specify Roles is checked, but no roles are selected")
+            }
+            SelectionError::UnknownRole(role) => trf("Unknown role: {}", &[role]),
+            SelectionError::UnknownSite(site) => trf("Unknown site: {}", &[site]),
+            SelectionError::NoPlatformsSelected => {
+                tr("Specific platforms were requested, but none are selected")
+            }
+            SelectionError::UnknownPlatform(platform) => trf("Unknown platform: {}", &[platform]),
+            SelectionError::InvalidLevel(level) => trf("Invalid level: {}", &[level]),
+            SelectionError::UnknownSequence(seq) => trf("Unknown sequence: {}", &[seq]),
+            SelectionError::UnknownShot { sequence, shot } => {
+                trf("Unknown shot: {} in sequence {}", &[shot, sequence])
+            }
+            SelectionError::MissingComment => tr("A comment is required"),
+            SelectionError::MissingTicket => tr("A ticket is required"),
+            SelectionError::InvalidTicket { ticket, pattern } => {
+                trf("Invalid ticket: {} does not match {}", &[ticket, pattern])
+            }
+            SelectionError::InvalidTicketPattern { pattern, error } => {
+                trf("Invalid ticket pattern {}: {}", &[pattern, error])
+            }
+            SelectionError::InvalidDate(date) => {
+                trf("Invalid date: {}. Expected YYYY-MM-DD", &[date])
+            }
+            SelectionError::ExpiryNotAfterEffective {
+                effective_from,
+                expires_on,
+            } => trf(
+                "The expiry date {} must be after the effective date {}",
+                &[expires_on, effective_from],
+            ),
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+impl Error for SelectionError {}
+
+/// SelectionWarning describes why a valid VpinSelection may nonetheless be a mistake
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectionWarning {
+    /// The pins apply to the whole show, and never expire
+    NoExpiryOnShow(String),
+}
+
+impl fmt::Display for SelectionWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            SelectionWarning::NoExpiryOnShow(show) => trf(
+                "The pins apply to the whole of {} and never expire",
+                &[show],
+            ),
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+/// SelectionContext holds the distributions, roles, levels, sites and platforms a
+/// selection may be made from, along with the rules for its comment and ticket.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectionContext {
+    /// The distributions on offer. When empty, any distribution is accepted.
+    pub distributions: Vec<String>,
+    /// The roles on offer
+    pub roles: Vec<String>,
+    /// The sequences and shots on offer
+    pub levels: LevelMap,
+    /// The sites on offer
+    pub sites: Vec<String>,
+    /// The platforms on offer
+    pub platforms: Vec<String>,
+    /// Whether a comment must be given
+    pub comment_required: bool,
+    /// Whether a ticket must be given
+    pub ticket_required: bool,
+    /// Some regular expression the whole ticket must match (eg `[A-Z]+-[0-9]+`)
+    pub ticket_pattern: Option<String>,
+}
+
+impl SelectionContext {
+    /// Validate the supplied selection against the context
+    ///
+    /// # Arguments
+    /// * `selection` - The VpinSelection to validate
+    ///
+    /// # Returns
+    /// * Ok if the selection could have been made in the dialog
+    /// * SelectionError describing the first problem otherwise
+    pub fn validate(&self, selection: &VpinSelection) -> Result<(), SelectionError> {
+        if selection.show.is_empty() {
+            return Err(SelectionError::MissingShow);
+        }
+        if selection.distributions.is_empty()
+            || selection.distributions.iter().any(String::is_empty)
+        {
+            return Err(SelectionError::MissingDistribution);
+        }
+        if !self.distributions.is_empty() {
+            if let Some(distribution) = selection
+                .distributions
+                .iter()
+                .find(|distribution| !self.distributions.contains(distribution))
+            {
+                return Err(SelectionError::UnknownDistribution(distribution.clone()));
+            }
+        }
+        if let Some(roles) = &selection.roles {
+            if roles.is_empty() {
+                return Err(SelectionError::NoRolesSelected);
+            }
+            if let Some(role) = roles.iter().find(|role| !self.roles.contains(role)) {
+                return Err(SelectionError::UnknownRole(role.clone()));
+            }
+        }
+        if let Some(level) = &selection.level {
+            self.validate_level(selection.show.as_str(), level.as_str())?;
+        }
+        if let Some(site) = &selection.site {
+            if !self.sites.contains(site) {
+                return Err(SelectionError::UnknownSite(site.clone()));
+            }
+        }
+        if let Some(platforms) = &selection.platforms {
+            if platforms.is_empty() {
+                return Err(SelectionError::NoPlatformsSelected);
+            }
+            if let Some(platform) = platforms
+                .iter()
+                .find(|platform| !self.platforms.contains(platform))
+            {
+                return Err(SelectionError::UnknownPlatform(platform.clone()));
+            }
+        }
+        self.validate_comment(selection)?;
+        self.validate_ticket(selection)?;
+        validate_dates(selection)
+    }
+
+    // Validate that a comment is given, when required
+    fn validate_comment(&self, selection: &VpinSelection) -> Result<(), SelectionError> {
+        let has_comment = selection
+            .comment
+            .as_ref()
+            .map(|comment| !comment.trim().is_empty())
+            .unwrap_or(false);
+        if self.comment_required && !has_comment {
+            return Err(SelectionError::MissingComment);
+        }
+        Ok(())
+    }
+
+    // Validate that a ticket is given, when required, and that it matches the pattern
+    fn validate_ticket(&self, selection: &VpinSelection) -> Result<(), SelectionError> {
+        let ticket = match selection.ticket.as_deref().map(str::trim) {
+            Some(ticket) if !ticket.is_empty() => ticket,
+            _ if self.ticket_required => return Err(SelectionError::MissingTicket),
+            _ => return Ok(()),
+        };
+        if let Some(pattern) = &self.ticket_pattern {
+            let regex = ticket_regex(pattern.as_str())?;
+            if !regex.is_match(ticket) {
+                return Err(SelectionError::InvalidTicket {
+                    ticket: ticket.to_string(),
+                    pattern: pattern.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // Validate that the level is either show.seq or show.seq.shot, and that the
+    // sequence and shot are known
+    fn validate_level(&self, show: &str, level: &str) -> Result<(), SelectionError> {
+        let pieces = level.split('.').collect::<Vec<_>>();
+        if pieces.len() < 2 || pieces.len() > 3 || pieces[0] != show {
+            return Err(SelectionError::InvalidLevel(level.to_string()));
+        }
+        let shots = self
+            .levels
+            .get(pieces[1])
+            .ok_or_else(|| SelectionError::UnknownSequence(pieces[1].to_string()))?;
+        if pieces.len() == 3 && !shots.iter().any(|shot| shot == pieces[2]) {
+            return Err(SelectionError::UnknownShot {
+                sequence: pieces[1].to_string(),
+                shot: pieces[2].to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Find the warnings which apply to a selection. These do not prevent the pins from
+/// being created.
+///
+/// # Arguments
+/// * `selection` - The VpinSelection to check
+///
+/// # Returns
+/// * Vector of SelectionWarning
+pub fn warnings(selection: &VpinSelection) -> Vec<SelectionWarning> {
+    let mut warnings = Vec::new();
+    if selection.level.is_none() && selection.expires_on.is_none() {
+        warnings.push(SelectionWarning::NoExpiryOnShow(selection.show.clone()));
+    }
+    warnings
+}
+
+// Validate that the dates are well formed, and that the pins expire after they
+// take effect
+fn validate_dates(selection: &VpinSelection) -> Result<(), SelectionError> {
+    for date in selection
+        .effective_from
+        .iter()
+        .chain(selection.expires_on.iter())
+    {
+        if !is_valid_date(date.as_str()) {
+            return Err(SelectionError::InvalidDate(date.clone()));
+        }
+    }
+    if let (Some(effective_from), Some(expires_on)) =
+        (&selection.effective_from, &selection.expires_on)
+    {
+        // dates of the form YYYY-MM-DD order as strings
+        if expires_on <= effective_from {
+            return Err(SelectionError::ExpiryNotAfterEffective {
+                effective_from: effective_from.clone(),
+                expires_on: expires_on.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+// Determine whether the date is a calendar date of the form YYYY-MM-DD
+fn is_valid_date(date: &str) -> bool {
+    let pieces = date.split('-').collect::<Vec<_>>();
+    let well_formed = pieces.len() == 3
+        && pieces
+            .iter()
+            .zip(&[4, 2, 2])
+            .all(|(piece, len)| piece.len() == *len && piece.bytes().all(|b| b.is_ascii_digit()));
+    if !well_formed {
+        return false;
+    }
+    let (year, month, day) = match (
+        pieces[0].parse::<u32>(),
+        pieces[1].parse::<u32>(),
+        pieces[2].parse::<u32>(),
+    ) {
+        (Ok(year), Ok(month), Ok(day)) => (year, month, day),
+        _ => return false,
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    day >= 1 && day <= days
+}
+
+/// Compile a ticket pattern into a regular expression which must match the whole ticket
+///
+/// # Arguments
+/// * `pattern` - The pattern (eg `[A-Z]+-[0-9]+`)
+///
+/// # Returns
+/// * The Regex
+/// * SelectionError::InvalidTicketPattern if the pattern is invalid
+pub fn ticket_regex(pattern: &str) -> Result<Regex, SelectionError> {
+    // the pattern is checked alone first, so that any error refers to it as written
+    Regex::new(pattern)
+        .and_then(|_| Regex::new(format!("^(?:{})$", pattern).as_str()))
+        .map_err(|err| SelectionError::InvalidTicketPattern {
+            pattern: pattern.to_string(),
+            error: err.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> SelectionContext {
+        let mut levels = LevelMap::new();
+        levels.insert(
+            "RD".to_string(),
+            vec!["0001".to_string(), "0002".to_string()],
+        );
+        SelectionContext {
+            distributions: vec!["modelpublish-1.2.0".to_string()],
+            roles: vec!["anim".to_string(), "fx".to_string()],
+            levels,
+            sites: vec!["portland".to_string()],
+            platforms: vec!["linux-el7".to_string(), "linux-el9".to_string()],
+            ..SelectionContext::default()
+        }
+    }
+
+    fn selection() -> VpinSelection {
+        VpinSelection {
+            show: "DEV01".to_string(),
+            distributions: vec!["modelpublish-1.2.0".to_string()],
+            roles: None,
+            level: None,
+            site: None,
+            platforms: None,
+            comment: None,
+            ticket: None,
+            effective_from: None,
+            expires_on: None,
+        }
+    }
+
+    #[test]
+    fn accepts_a_selection_which_could_be_made_in_the_dialog() {
+        let selection = VpinSelection {
+            roles: Some(vec!["fx".to_string()]),
+            level: Some("DEV01.RD.0002".to_string()),
+            site: Some("portland".to_string()),
+            platforms: Some(vec!["linux-el9".to_string()]),
+            ..selection()
+        };
+        assert_eq!(context().validate(&selection), Ok(()));
+        assert_eq!(context().validate(&self::selection()), Ok(()));
+    }
+
+    #[test]
+    fn rejects_a_missing_show() {
+        let selection = VpinSelection {
+            show: String::new(),
+            ..selection()
+        };
+        assert_eq!(
+            context().validate(&selection),
+            Err(SelectionError::MissingShow)
+        );
+    }
+
+    #[test]
+    fn rejects_a_missing_or_empty_distribution() {
+        for distributions in [vec![], vec![String::new()]] {
+            let selection = VpinSelection {
+                distributions,
+                ..selection()
+            };
+            assert_eq!(
+                context().validate(&selection),
+                Err(SelectionError::MissingDistribution)
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_an_unknown_distribution_unless_any_is_accepted() {
+        let selection = VpinSelection {
+            distributions: vec!["modelpublish-9.9.9".to_string()],
+            ..selection()
+        };
+        assert_eq!(
+            context().validate(&selection),
+            Err(SelectionError::UnknownDistribution(
+                "modelpublish-9.9.9".to_string()
+            ))
+        );
+        let any = SelectionContext {
+            distributions: Vec::new(),
+            ..context()
+        };
+        assert_eq!(any.validate(&selection), Ok(()));
+    }
+
+    #[test]
+    fn rejects_empty_or_unknown_roles() {
+        let none = VpinSelection {
+            roles: Some(Vec::new()),
+            ..selection()
+        };
+        assert_eq!(
+            context().validate(&none),
+            Err(SelectionError::NoRolesSelected)
+        );
+        let unknown = VpinSelection {
+            roles: Some(vec!["fx".to_string(), "lighting".to_string()]),
+            ..selection()
+        };
+        assert_eq!(
+            context().validate(&unknown),
+            Err(SelectionError::UnknownRole("lighting".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_an_unknown_site() {
+        let selection = VpinSelection {
+            site: Some("london".to_string()),
+            ..selection()
+        };
+        assert_eq!(
+            context().validate(&selection),
+            Err(SelectionError::UnknownSite("london".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_empty_or_unknown_platforms() {
+        let none = VpinSelection {
+            platforms: Some(Vec::new()),
+            ..selection()
+        };
+        assert_eq!(
+            context().validate(&none),
+            Err(SelectionError::NoPlatformsSelected)
+        );
+        let unknown = VpinSelection {
+            platforms: Some(vec!["macos".to_string()]),
+            ..selection()
+        };
+        assert_eq!(
+            context().validate(&unknown),
+            Err(SelectionError::UnknownPlatform("macos".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_a_malformed_level() {
+        for level in &["DEV01", "DEV02.RD", "DEV01.RD.0001.extra"] {
+            let selection = VpinSelection {
+                level: Some(level.to_string()),
+                ..selection()
+            };
+            assert_eq!(
+                context().validate(&selection),
+                Err(SelectionError::InvalidLevel(level.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_an_unknown_sequence_or_shot() {
+        let seq = VpinSelection {
+            level: Some("DEV01.AA".to_string()),
+            ..selection()
+        };
+        assert_eq!(
+            context().validate(&seq),
+            Err(SelectionError::UnknownSequence("AA".to_string()))
+        );
+        let shot = VpinSelection {
+            level: Some("DEV01.RD.9999".to_string()),
+            ..selection()
+        };
+        assert_eq!(
+            context().validate(&shot),
+            Err(SelectionError::UnknownShot {
+                sequence: "RD".to_string(),
+                shot: "9999".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn requires_a_comment_when_configured() {
+        let context = SelectionContext {
+            comment_required: true,
+            ..context()
+        };
+        for comment in [None, Some("  ".to_string())] {
+            let selection = VpinSelection {
+                comment,
+                ..selection()
+            };
+            assert_eq!(
+                context.validate(&selection),
+                Err(SelectionError::MissingComment)
+            );
+        }
+        let selection = VpinSelection {
+            comment: Some("hotfix for the farm".to_string()),
+            ..selection()
+        };
+        assert_eq!(context.validate(&selection), Ok(()));
+    }
+
+    #[test]
+    fn requires_a_ticket_when_configured() {
+        let context = SelectionContext {
+            ticket_required: true,
+            ..context()
+        };
+        assert_eq!(
+            context.validate(&selection()),
+            Err(SelectionError::MissingTicket)
+        );
+    }
+
+    #[test]
+    fn matches_the_whole_ticket_against_the_pattern() {
+        let context = SelectionContext {
+            ticket_pattern: Some("[A-Z]+-[0-9]+".to_string()),
+            ..context()
+        };
+        let valid = VpinSelection {
+            ticket: Some("PROJ-123".to_string()),
+            ..selection()
+        };
+        assert_eq!(context.validate(&valid), Ok(()));
+        let invalid = VpinSelection {
+            ticket: Some("see PROJ-123".to_string()),
+            ..selection()
+        };
+        assert_eq!(
+            context.validate(&invalid),
+            Err(SelectionError::InvalidTicket {
+                ticket: "see PROJ-123".to_string(),
+                pattern: "[A-Z]+-[0-9]+".to_string(),
+            })
+        );
+        // an optional ticket is not held to the pattern when absent
+        assert_eq!(context.validate(&selection()), Ok(()));
+    }
+
+    #[test]
+    fn rejects_an_invalid_ticket_pattern() {
+        let context = SelectionContext {
+            ticket_pattern: Some("[A-Z".to_string()),
+            ..context()
+        };
+        let selection = VpinSelection {
+            ticket: Some("PROJ-123".to_string()),
+            ..selection()
+        };
+        match context.validate(&selection) {
+            Err(SelectionError::InvalidTicketPattern { pattern, .. }) => {
+                assert_eq!(pattern, "[A-Z")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(ticket_regex("[A-Z").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        for date in &[
+            "2024-02-30",
+            "2023-02-29",
+            "2024-13-01",
+            "2024-1-01",
+            "tomorrow",
+        ] {
+            let selection = VpinSelection {
+                expires_on: Some(date.to_string()),
+                ..selection()
+            };
+            assert_eq!(
+                context().validate(&selection),
+                Err(SelectionError::InvalidDate(date.to_string()))
+            );
+        }
+        let leap = VpinSelection {
+            effective_from: Some("2024-02-29".to_string()),
+            ..selection()
+        };
+        assert_eq!(context().validate(&leap), Ok(()));
+    }
+
+    #[test]
+    fn rejects_an_expiry_which_is_not_after_the_effective_date() {
+        for expires_on in &["2024-03-01", "2024-02-01"] {
+            let selection = VpinSelection {
+                effective_from: Some("2024-03-01".to_string()),
+                expires_on: Some(expires_on.to_string()),
+                ..selection()
+            };
+            assert_eq!(
+                context().validate(&selection),
+                Err(SelectionError::ExpiryNotAfterEffective {
+                    effective_from: "2024-03-01".to_string(),
+                    expires_on: expires_on.to_string(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn warns_about_show_wide_pins_which_never_expire() {
+        assert_eq!(
+            warnings(&selection()),
+            vec![SelectionWarning::NoExpiryOnShow("DEV01".to_string())]
+        );
+        let expiring = VpinSelection {
+            expires_on: Some("2030-01-01".to_string()),
+            ..selection()
+        };
+        assert!(warnings(&expiring).is_empty());
+        let shot = VpinSelection {
+            level: Some("DEV01.RD.0001".to_string()),
+            ..selection()
+        };
+        assert!(warnings(&shot).is_empty());
+    }
+}
@@ -1,19 +1,57 @@
+pub use crate::cache::{CacheKey, CacheStats, CachingProvider};
+pub use crate::choice::Choice;
+pub use crate::conflict::{Conflict, ConflictKind};
+use crate::data_provider::lock;
+pub use crate::data_provider::DataProvider;
+pub use crate::distribution::{Version, VersionChange};
+use crate::i18n::trf;
 use crate::inner_vpin_dialog::InnerVpinDialog;
 pub use crate::inner_vpin_dialog::LevelMap;
-use crate::inner_vpin_dialog::DEFAULT_SITE;
-use qt_core::{QString, Signal, SlotOfQString};
+pub use crate::loader::Section;
+use crate::loader::{LoadResult, Loaded, Loader};
+pub use crate::pin_provider::PinProvider;
+use crate::pin_sink::write_pins;
+pub use crate::pin_sink::{CommitError, MemorySink, PinSink, SinkError};
+pub use crate::pin_spec::PinSpec;
+pub use crate::resolver::PinChange;
+pub use crate::shortcuts::{ShortcutAction, ShortcutMap};
+pub use crate::theme::Theme;
+use crate::validation::warnings;
+pub use crate::validation::{SelectionContext, SelectionError, SelectionWarning};
+pub use crate::vpin_selection::VpinSelection;
+use qt_core::{QTimer, Signal, Slot, SlotOfInt};
+use qt_gui::QKeySequence;
 use qt_widgets::{
-    cpp_core::{CastInto, MutPtr, Ptr, Ref},
-    QComboBox, QDialog, QWidget,
+    cpp_core::{CastInto, MutPtr, Ptr},
+    QComboBox, QDialog, QShortcut, QWidget,
 };
-use std::cell::RefCell;
+use rustqt_utils::qs;
+use std::cell::{Cell, RefCell};
+use std::io;
 use std::os::raw::c_int;
+use std::path::Path;
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+/// ApplyPolicy determines what happens to the fields of a non-modal dialog
+/// after the user presses Apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ApplyPolicy {
+    /// Leave the fields as they are, so that similar pins may be applied in succession
+    #[default]
+    Keep,
+    /// Return the fields to their initial state
+    Reset,
+}
+
+// How often the results of asynchronous loads are checked for, in milliseconds
+const LOAD_POLL_INTERVAL: c_int = 50;
 
 /// VpinDialog combines an InnerVpinDialog instance, which it exposes through
 /// an immutable interace using the Rusty Interior Mutability Pattern, along with
-/// a Slot to handle internal state changes which do not need to be exposed to the
-/// outside.
+/// the Slots registered through `on_accept` and `on_reject`. The controls themselves
+/// live in a VpinSelectorWidget, which handles its own internal state changes and
+/// may also be embedded directly in other layouts.
 ///
 /// This nested construction serves two purposes
 /// * limits the periods of mutability
@@ -36,29 +74,15 @@ use std::rc::Rc;
 ///
 ///       let dialog = Rc::new(create_dialog("DEV01", "modelpublish-1.2.0", main_ptr));
 ///
-///      // Create an accepted slot
-///      let accepted_slot = Slot::new(enclose! { (dialog) move || {
-///         if let Some(roles) = dialog.selected_roles() {
-///             println!("roles: {:?}", roles);
-///         } else {
-///             println!("roles: any");
-///         }
-///         if let Some(selected_level) = dialog.selected_level() {
-///             println!("level: {:?}", selected_level);
-///         } else {
-///             println!("level: {}", dialog.show_name());
-///         }
-///         match dialog.selected_site(){
-///             Some(site) => println!(
-///                 "site:  {}", site
-///             ),
-///             None => println!("site:  Any"),
-///         }
-///         dialog.accept();
-///      }});
-///
-///      // Connect the accepted signal to the accepted slot
-///      dialog.accepted().connect(&accepted_slot);
+///      // Register a callback which is handed the selection when the user
+///      // presses OK. Returning Ok closes the dialog, returning Err keeps it
+///      // open and displays the error.
+///      dialog.on_accept(|selection: VpinSelection| {
+///         println!("roles: {:?}", selection.roles);
+///         println!("level: {}", selection.level_or_show());
+///         println!("site:  {:?}", selection.site);
+///         Ok(())
+///      });
 ///
 ///      let exec_dialog_slot = Slot::new(enclose! { (dialog) move || {
 ///         let result = dialog.dialog_mut().exec(); //
@@ -69,7 +93,17 @@ use std::rc::Rc;
 /// ```
 pub struct VpinDialog<'a> {
     dialog: Rc<RefCell<InnerVpinDialog<'a>>>,
-    seq_changed: SlotOfQString<'a>,
+    accept_slot: RefCell<Option<Slot<'a>>>,
+    reject_slot: RefCell<Option<Slot<'a>>>,
+    apply_slot: RefCell<Option<Slot<'a>>>,
+    refresh_slot: RefCell<Option<Slot<'a>>>,
+    preview_slot: Slot<'a>,
+    apply_policy: Rc<Cell<ApplyPolicy>>,
+    shortcuts: RefCell<Vec<(MutPtr<QShortcut>, Slot<'a>)>>,
+    loader: Rc<RefCell<Loader>>,
+    load_timer: MutPtr<QTimer>,
+    load_slot: Slot<'a>,
+    finished_slot: SlotOfInt<'a>,
 }
 
 impl<'a> VpinDialog<'a> {
@@ -92,19 +126,64 @@ impl<'a> VpinDialog<'a> {
             distribution,
             parent,
         )));
-        let ivd = inner_vpin_dialog.clone();
-        let seq_changed = SlotOfQString::new(move |idx: Ref<QString>| {
-            let sequence = idx.to_std_string();
-            ivd.borrow().set_shots_for_seq(sequence.as_str());
-        });
+        let preview_dialog = inner_vpin_dialog.clone();
+        let load_dialog = inner_vpin_dialog.clone();
+        let finished_dialog = inner_vpin_dialog.clone();
+        let loader = Rc::new(RefCell::new(Loader::new()));
+        let load_loader = loader.clone();
+        let finished_loader = loader.clone();
+        // the timer is owned by the dialog
+        let load_timer = QTimer::new_1a(inner_vpin_dialog.borrow_mut().dialog_mut()).into_ptr();
         let dialog = VpinDialog {
             dialog: inner_vpin_dialog,
-            seq_changed,
+            accept_slot: RefCell::new(None),
+            reject_slot: RefCell::new(None),
+            apply_slot: RefCell::new(None),
+            refresh_slot: RefCell::new(None),
+            // the preview is refreshed whenever the selection changes. The selection
+            // may change while the dialog is mutably borrowed, in which case the
+            // preview is refreshed by whoever holds the borrow
+            preview_slot: Slot::new(move || {
+                if let Ok(dialog) = preview_dialog.try_borrow() {
+                    dialog.update_preview();
+                }
+            }),
+            apply_policy: Rc::new(Cell::new(ApplyPolicy::default())),
+            shortcuts: RefCell::new(Vec::new()),
+            loader,
+            load_timer,
+            // the results of asynchronous loads are applied on the GUI thread as
+            // they arrive
+            load_slot: Slot::new(move || {
+                Self::apply_loaded(&load_dialog, &load_loader, load_timer);
+            }),
+            // loading stops once the dialog is closed
+            finished_slot: SlotOfInt::new(move |_result: c_int| {
+                Self::stop_loading(&finished_dialog, &finished_loader, load_timer);
+            }),
         };
+        dialog.load_timer.timeout().connect(&dialog.load_slot);
+        dialog
+            .dialog
+            .borrow()
+            .finished()
+            .connect(&dialog.finished_slot);
         dialog
-            .seqs_cb()
-            .current_index_changed2()
-            .connect(&dialog.seq_changed);
+            .dialog
+            .borrow()
+            .selector()
+            .connect_selection_changed(&dialog.preview_slot);
+        dialog
+            .dialog
+            .borrow()
+            .downgrade_toggled()
+            .connect(&dialog.preview_slot);
+        dialog
+            .dialog
+            .borrow()
+            .connect_dates_changed(&dialog.preview_slot);
+        dialog.update_preview();
+        dialog.set_shortcuts(ShortcutMap::default());
         dialog
     }
 
@@ -120,6 +199,440 @@ impl<'a> VpinDialog<'a> {
         self.dialog.borrow().accepted()
     }
 
+    /// Register a callback to be invoked with the current selection when the user
+    /// presses OK. The selection is first validated against the roles, levels and
+    /// sites on offer, and any problem is displayed without invoking the callback.
+    /// Downgrades of the version in effect must be confirmed via the "Allow downgrade"
+    /// checkbox. Conflicts with existing pins are reported, and the callback is only
+    /// invoked once the user confirms them by pressing OK again. The dialog owns the
+    /// underlying Slot. If the callback returns `Ok`, the dialog is closed. If it
+    /// returns `Err`, the dialog stays open and displays the error message.
+    /// Registering a new callback replaces the previous one.
+    ///
+    /// # Arguments
+    /// * `callback` - Closure receiving the VpinSelection
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn on_accept<F>(&self, mut callback: F)
+    where
+        F: FnMut(VpinSelection) -> Result<(), String> + 'a,
+    {
+        let dialog = self.dialog.clone();
+        let accept_slot = Slot::new(move || {
+            let selection = match dialog.borrow().validated_selection() {
+                Ok(selection) => selection,
+                Err(err) => {
+                    dialog.borrow().set_error(Some(err.to_string().as_str()));
+                    return;
+                }
+            };
+            if !dialog.borrow().confirm_downgrades(&selection) {
+                return;
+            }
+            if !dialog.borrow().confirm_conflicts(&selection) {
+                return;
+            }
+            match callback(selection) {
+                Ok(()) => {
+                    dialog.borrow().set_error(None);
+                    // release the borrow before accepting, as accepting emits
+                    // signals whose slots may want to access the dialog
+                    let mut qdialog = dialog.borrow_mut().dialog_mut();
+                    qdialog.accept();
+                }
+                Err(err) => dialog.borrow().set_error(Some(err.as_str())),
+            }
+        });
+        self.accepted().connect(&accept_slot);
+        self.accept_slot.replace(Some(accept_slot));
+    }
+
+    /// Register a callback to be invoked when the user dismisses the dialog, either
+    /// via the Cancel button or the escape key. The dialog owns the underlying Slot.
+    /// Registering a new callback replaces the previous one.
+    ///
+    /// # Arguments
+    /// * `callback` - Closure invoked upon rejection
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn on_reject<F>(&self, callback: F)
+    where
+        F: FnMut() + 'a,
+    {
+        let reject_slot = Slot::new(callback);
+        self.dialog().rejected().connect(&reject_slot);
+        self.reject_slot.replace(Some(reject_slot));
+    }
+
+    /// Register a callback to be invoked with the current selection when the user
+    /// presses Apply. The Apply button is only available once the dialog has been made
+    /// non-modal via `set_modal(false)`. The dialog stays open regardless of the outcome.
+    /// As with OK, downgrades must be confirmed via the checkbox, and conflicts with
+    /// existing pins by pressing Apply again.
+    /// If the callback returns `Ok`, the fields are kept or reset according to the
+    /// ApplyPolicy. If it returns `Err`, the error message is displayed. Registering a
+    /// new callback replaces the previous one.
+    ///
+    /// # Arguments
+    /// * `callback` - Closure receiving the VpinSelection
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn on_apply<F>(&self, mut callback: F)
+    where
+        F: FnMut(VpinSelection) -> Result<(), String> + 'a,
+    {
+        let dialog = self.dialog.clone();
+        let apply_policy = self.apply_policy.clone();
+        let apply_slot = Slot::new(move || {
+            let selection = match dialog.borrow().validated_selection() {
+                Ok(selection) => selection,
+                Err(err) => {
+                    dialog.borrow().set_error(Some(err.to_string().as_str()));
+                    return;
+                }
+            };
+            if !dialog.borrow().confirm_downgrades(&selection) {
+                return;
+            }
+            if !dialog.borrow().confirm_conflicts(&selection) {
+                return;
+            }
+            match callback(selection) {
+                Ok(()) => {
+                    let dialog = dialog.borrow();
+                    dialog.set_error(None);
+                    if apply_policy.get() == ApplyPolicy::Reset {
+                        dialog.reset();
+                    }
+                    dialog.update_preview();
+                }
+                Err(err) => dialog.borrow().set_error(Some(err.as_str())),
+            }
+        });
+        self.dialog.borrow().applied().connect(&apply_slot);
+        self.apply_slot.replace(Some(apply_slot));
+    }
+
+    /// Register a callback to be invoked when the user presses Refresh, which is
+    /// offered once a callback is registered. The dialog owns the underlying Slot.
+    /// Registering a new callback replaces the previous one.
+    ///
+    /// # Arguments
+    /// * `callback` - Closure invoked upon refresh
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn on_refresh<F>(&self, mut callback: F)
+    where
+        F: FnMut() + 'a,
+    {
+        let refresh_slot = Slot::new(move || callback());
+        self.dialog.borrow().refreshed().connect(&refresh_slot);
+        self.dialog.borrow().set_refresh_visible(true);
+        self.refresh_slot.replace(Some(refresh_slot));
+    }
+
+    /// Write the pins the selection expands to through the sink when the user presses
+    /// OK or Apply, within a single transaction. Should any pin fail to be written,
+    /// the transaction is rolled back, the failing pin is marked in the preview, and
+    /// the dialog stays open displaying the error. Once written, the pins are added
+    /// to the existing pins. This replaces any callbacks registered via `on_accept`
+    /// and `on_apply`.
+    ///
+    /// # Arguments
+    /// * `sink` - The PinSink, shared so that the caller may inspect it afterwards
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn commit_to<S>(&self, sink: Rc<RefCell<S>>)
+    where
+        S: PinSink + 'a,
+    {
+        let accept_dialog = self.dialog.clone();
+        let accept_sink = sink.clone();
+        self.on_accept(move |selection: VpinSelection| {
+            Self::commit_selection(&accept_dialog, &mut *accept_sink.borrow_mut(), &selection)
+        });
+        let apply_dialog = self.dialog.clone();
+        self.on_apply(move |selection: VpinSelection| {
+            Self::commit_selection(&apply_dialog, &mut *sink.borrow_mut(), &selection)
+        });
+    }
+
+    // write the pins the selection expands to through the sink, marking the pins
+    // which fail in the preview, and adding the pins to the existing pins on success
+    unsafe fn commit_selection<S: PinSink + ?Sized>(
+        dialog: &Rc<RefCell<InnerVpinDialog<'a>>>,
+        sink: &mut S,
+        selection: &VpinSelection,
+    ) -> Result<(), String> {
+        let pins = selection.expand();
+        if let Err(err) = write_pins(sink, &pins) {
+            dialog.borrow().set_pin_errors(err.pin_errors());
+            return Err(err.to_string());
+        }
+        let mut existing_pins = dialog.borrow().existing_pins().to_vec();
+        existing_pins.extend(pins);
+        dialog.borrow_mut().set_existing_pins(existing_pins);
+        Ok(())
+    }
+
+    /// Set whether the dialog is modal. The dialog is modal by default. A non-modal
+    /// dialog offers an Apply button in addition to OK and Cancel, and should be
+    /// displayed with `show` rather than `exec`.
+    ///
+    /// # Arguments
+    /// * `modal` - Whether the dialog should be modal
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn set_modal(&self, modal: bool) {
+        self.dialog.borrow_mut().set_modal(modal);
+    }
+
+    /// Set the policy determining whether the fields are kept or reset after
+    /// a successful Apply
+    ///
+    /// # Arguments
+    /// * `policy` - The ApplyPolicy
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_apply_policy(&self, policy: ApplyPolicy) {
+        self.apply_policy.set(policy);
+    }
+
+    /// Display the dialog without blocking. This is the way to present a non-modal
+    /// dialog.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn show(&self) {
+        self.dialog.borrow_mut().show();
+    }
+
+    /// Replace the keyboard shortcuts. The dialog is created with
+    /// `ShortcutMap::default()`, and the bound shortcuts are listed in the tooltip
+    /// of the shortcuts help label.
+    ///
+    /// # Arguments
+    /// * `shortcuts` - The ShortcutMap to install
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn set_shortcuts(&self, shortcuts: ShortcutMap) {
+        for (mut shortcut, _slot) in self.shortcuts.borrow_mut().drain(..) {
+            shortcut.delete_later();
+        }
+        let mut installed = Vec::new();
+        for (action, keys) in shortcuts.iter() {
+            let mut shortcut =
+                QShortcut::new_2a(&QKeySequence::from_q_string(&qs(keys)), self.dialog_mut())
+                    .into_ptr();
+            let dialog = self.dialog.clone();
+            let slot = Slot::new(move || match action {
+                ShortcutAction::ToggleRoles => dialog.borrow().selector().toggle_roles(),
+                ShortcutAction::ToggleLevels => dialog.borrow().selector().toggle_levels(),
+                ShortcutAction::FocusRolesFilter => dialog.borrow().selector().focus_roles_filter(),
+                ShortcutAction::SelectAllRoles => dialog.borrow().selector().select_all_roles(),
+                ShortcutAction::SelectNoRoles => dialog.borrow().selector().select_no_roles(),
+                ShortcutAction::Accept => {
+                    // release the borrow before clicking, as the accept slot
+                    // needs to borrow the dialog mutably
+                    let mut ok_button = dialog.borrow().ok_button();
+                    ok_button.click();
+                }
+                ShortcutAction::Refresh => {
+                    let mut refresh_button = dialog.borrow().refresh_button();
+                    if !refresh_button.is_hidden() {
+                        refresh_button.click();
+                    }
+                }
+            });
+            shortcut.activated().connect(&slot);
+            installed.push((shortcut, slot));
+        }
+        self.dialog
+            .borrow()
+            .set_shortcuts_help(shortcuts.help_text().as_str());
+        self.shortcuts.replace(installed);
+    }
+
+    /// Retrieve the current state of the dialog as a VpinSelection
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * VpinSelection instance
+    pub unsafe fn selection(&self) -> VpinSelection {
+        self.dialog.borrow().selection()
+    }
+
+    /// Retrieve the current state of the dialog as a VpinSelection, validated
+    /// against the roles, levels and sites on offer
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * VpinSelection if valid
+    /// * SelectionError describing the problem otherwise
+    pub unsafe fn validated_selection(&self) -> Result<VpinSelection, SelectionError> {
+        self.dialog.borrow().validated_selection()
+    }
+
+    /// Retrieve the comment explaining why the pins are needed
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some comment, trimmed of surrounding whitespace
+    /// * None if the comment is blank
+    pub unsafe fn comment(&self) -> Option<String> {
+        self.dialog.borrow().comment()
+    }
+
+    /// Set the comment explaining why the pins are needed
+    ///
+    /// # Arguments
+    /// * `comment` - The comment
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn set_comment(&self, comment: &str) {
+        self.dialog.borrow().set_comment(comment);
+    }
+
+    /// Retrieve the ticket tracking the pins
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some ticket, trimmed of surrounding whitespace
+    /// * None if the ticket is blank
+    pub unsafe fn ticket(&self) -> Option<String> {
+        self.dialog.borrow().ticket()
+    }
+
+    /// Set the ticket tracking the pins
+    ///
+    /// # Arguments
+    /// * `ticket` - The ticket (eg `PROJ-123`)
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn set_ticket(&self, ticket: &str) {
+        self.dialog.borrow().set_ticket(ticket);
+    }
+
+    /// Retrieve the date from which the pins take effect
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some date as `YYYY-MM-DD`
+    /// * None if the pins take effect immediately
+    pub unsafe fn effective_from(&self) -> Option<String> {
+        self.dialog.borrow().effective_from()
+    }
+
+    /// Set the date from which the pins take effect
+    ///
+    /// # Arguments
+    /// * `date` - Some date as `YYYY-MM-DD`, or None for immediately
+    ///
+    /// # Returns
+    /// * Ok if the date was set
+    /// * SelectionError::InvalidDate if the date is not a valid `YYYY-MM-DD` date
+    pub unsafe fn set_effective_from(&self, date: Option<&str>) -> Result<(), SelectionError> {
+        self.dialog.borrow().set_effective_from(date)
+    }
+
+    /// Retrieve the date on which the pins expire
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Some date as `YYYY-MM-DD`
+    /// * None if the pins never expire
+    pub unsafe fn expires_on(&self) -> Option<String> {
+        self.dialog.borrow().expires_on()
+    }
+
+    /// Set the date on which the pins expire. It must be after the effective date
+    /// for the selection to be valid.
+    ///
+    /// # Arguments
+    /// * `date` - Some date as `YYYY-MM-DD`, or None for never
+    ///
+    /// # Returns
+    /// * Ok if the date was set
+    /// * SelectionError::InvalidDate if the date is not a valid `YYYY-MM-DD` date
+    pub unsafe fn set_expires_on(&self, date: Option<&str>) -> Result<(), SelectionError> {
+        self.dialog.borrow().set_expires_on(date)
+    }
+
+    /// Retrieve the warnings about the current selection, which are displayed in the
+    /// dialog but do not prevent the pins from being created
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of SelectionWarning
+    pub unsafe fn warnings(&self) -> Vec<SelectionWarning> {
+        warnings(&self.selection())
+    }
+
+    /// Set whether a comment must be given before the pins are created. The
+    /// comment is optional by default.
+    ///
+    /// # Arguments
+    /// * `required` - Whether the comment is required
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn set_comment_required(&self, required: bool) {
+        self.dialog.borrow().set_comment_required(required);
+    }
+
+    /// Set whether a ticket must be given before the pins are created. The
+    /// ticket is optional by default.
+    ///
+    /// # Arguments
+    /// * `required` - Whether the ticket is required
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn set_ticket_required(&self, required: bool) {
+        self.dialog.borrow().set_ticket_required(required);
+    }
+
+    /// Set the regular expression the whole ticket must match (eg `[A-Z]+-[0-9]+`).
+    /// A blank, optional ticket is not checked against the pattern.
+    ///
+    /// # Arguments
+    /// * `pattern` - Some pattern, or None to accept any ticket
+    ///
+    /// # Returns
+    /// * Ok if the pattern was set
+    /// * SelectionError::InvalidTicketPattern if the pattern is not a valid regular
+    ///   expression, in which case the current pattern is kept
+    pub unsafe fn set_ticket_pattern(&self, pattern: Option<&str>) -> Result<(), SelectionError> {
+        self.dialog.borrow().set_ticket_pattern(pattern)
+    }
+
     /// Dismiss the dialog using accept. This is a convenience for consumrs
     /// of this struct, to avoid having to drill down.
     ///
@@ -187,7 +700,7 @@ impl<'a> VpinDialog<'a> {
     /// * Some Vec of String if roles are selected
     /// * None otherwise
     pub unsafe fn selected_roles(&self) -> Option<Vec<String>> {
-        self.dialog.borrow().selected_roles()
+        self.dialog.borrow().selector().selected_roles()
     }
 
     /// Retrieve an Option wrapped current site, if specified
@@ -199,11 +712,31 @@ impl<'a> VpinDialog<'a> {
     /// * Some wrapped site name, if specified
     /// * None, if `any` site specified
     pub unsafe fn selected_site(&self) -> Option<String> {
-        let sel_site = self.dialog.borrow().selected_site();
-        if sel_site == DEFAULT_SITE {
-            return None;
-        }
-        Some(sel_site)
+        self.dialog.borrow().selector().selected_site()
+    }
+
+    /// Retrieve the list of the selected platforms
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vec of the selected platform names. An empty Vec indicates that the pins
+    ///   are not restricted to any platform
+    pub unsafe fn selected_platforms(&self) -> Vec<String> {
+        self.dialog.borrow().selector().selected_platforms()
+    }
+
+    /// Retrieve the Choice behind the current site
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Choice::Specific wrapping the site identifier, if specified
+    /// * Choice::Any, if `any` site specified
+    pub unsafe fn site_choice(&self) -> Choice {
+        self.dialog.borrow().selector().site_choice()
     }
     /// Return the show's name. Unfortunately, we have to disambiguate between
     /// the `show` widget name, and the model
@@ -214,7 +747,7 @@ impl<'a> VpinDialog<'a> {
     /// # Returns
     /// * The show name as a String
     pub fn show_name(&self) -> String {
-        self.dialog.borrow().show_name()
+        self.dialog.borrow().selector().show_name()
     }
 
     /// Set show name
@@ -222,7 +755,13 @@ impl<'a> VpinDialog<'a> {
     where
         I: Into<String>,
     {
-        self.dialog.borrow().set_show_name(new_name.into());
+        self.dialog
+            .borrow()
+            .selector()
+            .set_show_name(new_name.into());
+        unsafe {
+            self.update_preview();
+        }
     }
     /// Return the a Some wrapped Sequence/shot if the user has activated
     /// the checkbox and selected a sequence or shot. Otherwise, it returns
@@ -234,7 +773,7 @@ impl<'a> VpinDialog<'a> {
     /// # Returns
     /// * Option<String>
     pub unsafe fn selected_level(&self) -> Option<String> {
-        self.dialog.borrow().selected_level()
+        self.dialog.borrow().selector().selected_level()
     }
 
     /// Set the distribution name
@@ -245,10 +784,374 @@ impl<'a> VpinDialog<'a> {
     /// # Returns
     /// * None
     pub unsafe fn set_distribution(&self, distribution: &str) {
-        self.dialog.borrow().set_distribution(distribution);
+        self.dialog
+            .borrow()
+            .selector()
+            .set_distribution(distribution);
+        self.update_preview();
+    }
+
+    /// Set the distributions on offer, each of which starts out checked. Several
+    /// distributions are presented as a checkable list, so that they may be pinned
+    /// together.
+    ///
+    /// # Arguments
+    /// * `distributions` - The distribution names
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_distributions(&self, distributions: Vec<&str>) {
+        self.dialog
+            .borrow()
+            .selector()
+            .set_distributions(distributions);
+        unsafe {
+            self.update_preview();
+        }
+    }
+
+    /// Set the pins already in place. The pins the selection expands to are
+    /// checked against them, and any conflicts are highlighted in the preview
+    /// and must be confirmed before the callbacks are invoked.
+    ///
+    /// # Arguments
+    /// * `pins` - The existing pins
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn set_existing_pins(&self, pins: Vec<PinSpec>) {
+        self.dialog.borrow_mut().set_existing_pins(pins);
+    }
+
+    /// Load the pins already in place for the show from the provider, replacing
+    /// any existing pins previously set
+    ///
+    /// # Arguments
+    /// * `provider` - The PinProvider
+    ///
+    /// # Returns
+    /// * Ok if the pins were loaded
+    /// * SinkError if the provider failed
+    pub unsafe fn load_existing_pins<P: PinProvider + ?Sized>(
+        &self,
+        provider: &mut P,
+    ) -> Result<(), SinkError> {
+        let pins = provider.existing_pins(self.show_name().as_str())?;
+        self.set_existing_pins(pins);
+        Ok(())
     }
 
-    /// Load the default stylesheet
+    /// Load the roles, sites, platforms, and the sequences and shots of the show, from
+    /// the provider. Nothing is set unless every request succeeds.
+    ///
+    /// # Arguments
+    /// * `provider` - The DataProvider
+    ///
+    /// # Returns
+    /// * Ok if the data was loaded
+    /// * SinkError if the provider failed
+    pub unsafe fn load_data<P: DataProvider + ?Sized>(
+        &self,
+        provider: &mut P,
+    ) -> Result<(), SinkError> {
+        let roles = provider.roles()?;
+        let sites = provider.sites()?;
+        let platforms = provider.platforms()?;
+        let levels = provider.levels(self.show_name().as_str())?;
+        self.set_roles(roles.iter().map(String::as_str).collect());
+        self.set_sites(sites.iter().map(String::as_str).collect());
+        self.set_platforms(platforms.iter().map(String::as_str).collect());
+        self.set_levels(levels);
+        Ok(())
+    }
+
+    /// Load the roles, sites, platforms, and the sequences and shots of the show from
+    /// the provider on a worker thread, so that the dialog stays responsive. Each section
+    /// displays a loading indicator, and its controls are disabled, until its data
+    /// arrives. Any failure is displayed in the dialog. Loading stops when the dialog
+    /// is closed.
+    ///
+    /// # Arguments
+    /// * `provider` - The DataProvider, which is moved to the worker thread
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn load_data_async<P>(&self, provider: P)
+    where
+        P: DataProvider + Send + 'static,
+    {
+        self.loader
+            .borrow_mut()
+            .load_data(provider, self.show_name().as_str());
+        self.start_loading(&[
+            Section::Roles,
+            Section::Sites,
+            Section::Platforms,
+            Section::Levels,
+        ]);
+    }
+
+    /// Load the roles, sites, platforms, and the sequences and shots of the show
+    /// through the cache on a worker thread, as `load_data_async` does. Pressing
+    /// Refresh abandons any load in progress, then empties the cache and loads the
+    /// data again on the worker. Refresh is disabled while the existing pins load, as
+    /// abandoning them would leave conflicts undetected. This replaces any callback
+    /// registered via `on_refresh`.
+    ///
+    /// # Arguments
+    /// * `provider` - The CachingProvider, shared so that the caller may inspect
+    ///   its CacheStats
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn load_data_cached<P>(&self, provider: Arc<Mutex<CachingProvider<P>>>)
+    where
+        P: DataProvider + Send + 'static,
+    {
+        self.load_data_async(provider.clone());
+        let dialog = self.dialog.clone();
+        let loader = self.loader.clone();
+        let load_timer = self.load_timer;
+        self.on_refresh(move || {
+            // abandon the load in progress, and empty the cache on the worker, which
+            // may have to wait for a request holding the provider to finish
+            Self::stop_loading(&dialog, &loader, load_timer);
+            let show = dialog.borrow().selector().show_name();
+            loader
+                .borrow_mut()
+                .load_data_with(provider.clone(), show.as_str(), |provider| {
+                    lock(provider)?.clear()?;
+                    Ok(())
+                });
+            Self::start_loading_sections(
+                &dialog,
+                load_timer,
+                &[
+                    Section::Roles,
+                    Section::Sites,
+                    Section::Platforms,
+                    Section::Levels,
+                ],
+            );
+        });
+    }
+
+    /// Load the pins already in place for the show from the provider on a worker
+    /// thread. OK and Apply are disabled until the pins arrive, so that conflicts
+    /// are not missed. Loading stops when the dialog is closed.
+    ///
+    /// # Arguments
+    /// * `provider` - The PinProvider, which is moved to the worker thread
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn load_existing_pins_async<P>(&self, provider: P)
+    where
+        P: PinProvider + Send + 'static,
+    {
+        self.loader
+            .borrow_mut()
+            .load_existing_pins(provider, self.show_name().as_str());
+        self.start_loading(&[Section::ExistingPins]);
+    }
+
+    /// Determine whether any asynchronous load has yet to deliver its data
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * bool
+    pub fn is_loading(&self) -> bool {
+        !self.loader.borrow().is_idle()
+    }
+
+    /// Abandon any asynchronous loads, re-enabling the sections awaiting data
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn cancel_loading(&self) {
+        Self::stop_loading(&self.dialog, &self.loader, self.load_timer);
+    }
+
+    // mark the sections as loading, and poll for their data
+    unsafe fn start_loading(&self, sections: &[Section]) {
+        Self::start_loading_sections(&self.dialog, self.load_timer, sections);
+    }
+
+    // mark the sections as loading, and start the timer polling for their data
+    unsafe fn start_loading_sections(
+        dialog: &Rc<RefCell<InnerVpinDialog<'a>>>,
+        mut load_timer: MutPtr<QTimer>,
+        sections: &[Section],
+    ) {
+        for section in sections {
+            dialog.borrow().set_section_loading(*section, true);
+        }
+        if !load_timer.is_active() {
+            load_timer.start_1a(LOAD_POLL_INTERVAL);
+        }
+    }
+
+    // apply the data which has arrived, and stop polling once every load is done
+    unsafe fn apply_loaded(
+        dialog: &Rc<RefCell<InnerVpinDialog<'a>>>,
+        loader: &Rc<RefCell<Loader>>,
+        mut load_timer: MutPtr<QTimer>,
+    ) {
+        let results = loader.borrow_mut().poll();
+        if loader.borrow().is_idle() {
+            load_timer.stop();
+        }
+        for LoadResult { section, result } in results {
+            dialog.borrow().set_section_loading(section, false);
+            match result {
+                Ok(Loaded::Roles(roles)) => dialog
+                    .borrow()
+                    .selector()
+                    .set_roles(roles.iter().map(String::as_str).collect()),
+                Ok(Loaded::Sites(sites)) => dialog
+                    .borrow()
+                    .selector()
+                    .set_sites(sites.iter().map(String::as_str).collect()),
+                Ok(Loaded::Platforms(platforms)) => dialog
+                    .borrow()
+                    .selector()
+                    .set_platforms(platforms.iter().map(String::as_str).collect()),
+                Ok(Loaded::Levels(levels)) => dialog.borrow().selector().set_levels(levels),
+                Ok(Loaded::ExistingPins(pins)) => dialog.borrow_mut().set_existing_pins(pins),
+                Err(err) => dialog
+                    .borrow()
+                    .set_error(Some(Self::load_error(section, err.as_str()).as_str())),
+            }
+        }
+    }
+
+    // abandon the loads in progress, and hide their loading indicators
+    unsafe fn stop_loading(
+        dialog: &Rc<RefCell<InnerVpinDialog<'a>>>,
+        loader: &Rc<RefCell<Loader>>,
+        mut load_timer: MutPtr<QTimer>,
+    ) {
+        load_timer.stop();
+        let sections = [
+            Section::Roles,
+            Section::Levels,
+            Section::Sites,
+            Section::Platforms,
+            Section::ExistingPins,
+        ];
+        for section in &sections {
+            if loader.borrow().is_loading(*section) {
+                dialog.borrow().set_section_loading(*section, false);
+            }
+        }
+        loader.borrow_mut().cancel();
+    }
+
+    // describe the failure to load a section
+    fn load_error(section: Section, error: &str) -> String {
+        match section {
+            Section::Roles => trf("Unable to load the roles: {}", &[&error]),
+            Section::Levels => trf("Unable to load the sequences and shots: {}", &[&error]),
+            Section::Sites => trf("Unable to load the sites: {}", &[&error]),
+            Section::Platforms => trf("Unable to load the platforms: {}", &[&error]),
+            Section::ExistingPins => trf("Unable to load the existing pins: {}", &[&error]),
+        }
+    }
+
+    /// Retrieve the pins already in place
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of PinSpec
+    pub fn existing_pins(&self) -> Vec<PinSpec> {
+        self.dialog.borrow().existing_pins().to_vec()
+    }
+
+    /// Find the conflicts between the pins the current selection expands to and
+    /// the existing pins
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of Conflicts
+    pub unsafe fn conflicts(&self) -> Vec<Conflict> {
+        let dialog = self.dialog.borrow();
+        dialog.conflicts(&dialog.selection())
+    }
+
+    /// Resolve the pin currently in effect for the package of each selected
+    /// distribution, at the selected level, role and site
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of (package name, Some winning PinSpec or None) tuples
+    pub unsafe fn resolutions(&self) -> Vec<(String, Option<PinSpec>)> {
+        self.dialog.borrow().resolutions()
+    }
+
+    /// Compare each pin the current selection expands to against the pin currently
+    /// in effect in its scope
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of PinChange, for each pin whose scope resolves to a comparable version
+    pub unsafe fn version_changes(&self) -> Vec<PinChange> {
+        let dialog = self.dialog.borrow();
+        dialog.version_changes(&dialog.selection())
+    }
+
+    /// Refresh the list of pins the current selection expands to. This happens
+    /// automatically as the user changes the selection.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn update_preview(&self) {
+        self.dialog.borrow().update_preview();
+    }
+
+    /// Retrieve the checked distributions
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of distribution names
+    pub unsafe fn selected_distributions(&self) -> Vec<String> {
+        self.dialog.borrow().selector().selected_distributions()
+    }
+
+    /// Check the supplied distributions, and uncheck the rest
+    ///
+    /// # Arguments
+    /// * `distributions` - The distribution names to check
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn set_selected_distributions(&self, distributions: &[&str]) {
+        self.dialog
+            .borrow()
+            .selector()
+            .set_selected_distributions(distributions);
+    }
+
+    /// Load the default stylesheet. The `PBGUI_VPIN_STYLESHEET` environment variable
+    /// may be used to substitute a stylesheet on disk, and `PBGUI_VPIN_WATCH_STYLESHEET`
+    /// to re-apply it whenever it changes.
     ///
     /// # Arguments
     /// * None
@@ -259,6 +1162,58 @@ impl<'a> VpinDialog<'a> {
         self.dialog.borrow_mut().set_default_stylesheet();
     }
 
+    /// Apply one of the built in themes, which set both the stylesheet and the
+    /// palette of the dialog. `Theme::Inherit` adopts the palette of the parent
+    /// widget, so that the dialog matches the host application.
+    ///
+    /// # Arguments
+    /// * `theme` - The Theme to apply
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn set_theme(&self, theme: Theme) {
+        self.dialog.borrow_mut().set_theme(theme);
+    }
+
+    /// Load the stylesheet from the supplied path, replacing the default
+    ///
+    /// # Arguments
+    /// * `path` - The path to the qss file
+    ///
+    /// # Returns
+    /// * Ok if the stylesheet was applied
+    /// * io::Error if the file could not be read
+    pub unsafe fn set_stylesheet_from_path<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.dialog
+            .borrow_mut()
+            .set_stylesheet_from_path(path.as_ref())
+    }
+
+    /// Load the stylesheet from the supplied path, and re-apply it whenever the
+    /// file changes on disk, so that the look of the dialog may be iterated upon
+    /// without recompiling. This replaces any stylesheet currently being watched.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the qss file
+    ///
+    /// # Returns
+    /// * Ok if the stylesheet was applied
+    /// * io::Error if the file could not be read
+    pub unsafe fn watch_stylesheet<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.dialog.borrow_mut().watch_stylesheet(path.as_ref())
+    }
+
+    /// Stop watching the stylesheet registered via `watch_stylesheet`
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * None
+    pub fn unwatch_stylesheet(&self) {
+        self.dialog.borrow_mut().unwatch_stylesheet();
+    }
+
     /// Set the sites. This should be done before calling exec on
     /// the inner dialog.
     ///
@@ -268,7 +1223,32 @@ impl<'a> VpinDialog<'a> {
     /// # Returns
     /// * None
     pub fn set_sites(&self, sites: Vec<&str>) {
-        self.dialog.borrow().set_sites(sites);
+        self.dialog.borrow().selector().set_sites(sites);
+    }
+
+    /// Set the sites along with the label displayed for each, eg
+    /// `("mtl", "Montreal (MTL)")`. This should be done before calling
+    /// exec on the inner dialog.
+    ///
+    /// # Arguments
+    /// * The (site identifier, label) pairs as a vector
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_site_labels(&self, sites: Vec<(&str, &str)>) {
+        self.dialog.borrow().selector().set_site_labels(sites);
+    }
+
+    /// Set the platforms (eg `linux-el9`). The platforms are only offered once
+    /// some are set. This should be done before calling exec on the inner dialog.
+    ///
+    /// # Arguments
+    /// * The platform names as a vector of &str
+    ///
+    /// # Returns
+    /// * None
+    pub fn set_platforms(&self, platforms: Vec<&str>) {
+        self.dialog.borrow().selector().set_platforms(platforms);
     }
 
     /// set the list of roles. This should before calling exec
@@ -280,7 +1260,33 @@ impl<'a> VpinDialog<'a> {
     /// # Returns
     /// * None
     pub fn set_roles(&self, roles: Vec<&str>) {
-        self.dialog.borrow().set_roles(roles);
+        self.dialog.borrow().selector().set_roles(roles);
+    }
+
+    /// Retrieve the object names of any interactive widgets which lack an
+    /// accessible name, for use in automated accessibility checks. This should
+    /// always be empty.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of object names
+    pub unsafe fn unlabelled_widgets(&self) -> Vec<String> {
+        self.dialog.borrow().unlabelled_widgets()
+    }
+
+    /// Select the supplied roles, deselecting the rest, and check "Specify Roles".
+    /// This should be done after calling `set_roles`. Roles which are not on offer
+    /// are ignored.
+    ///
+    /// # Arguments
+    /// * `roles` - The names of the roles to select
+    ///
+    /// # Returns
+    /// * None
+    pub unsafe fn set_selected_roles(&self, roles: &[&str]) {
+        self.dialog.borrow().selector().set_selected_roles(roles);
     }
 
     /// Retrieve a mutable pointer to the sequences QComboBox
@@ -291,7 +1297,7 @@ impl<'a> VpinDialog<'a> {
     /// # Returns
     /// * MutPtr wrapping the QComboBox for the sequences
     pub fn seqs_cb(&self) -> MutPtr<QComboBox> {
-        self.dialog.borrow().seqs_cb()
+        self.dialog.borrow().selector().seqs_cb()
     }
 
     // /// Given a vector of Strings, set levels
@@ -319,7 +1325,7 @@ impl<'a> VpinDialog<'a> {
     // * `levels` - A LevelMap whose keys are Sequences and whose shots are
     //              vectors of shots
     fn set_levels_map(&self, levels: LevelMap) {
-        self.dialog.borrow_mut().set_levels_map(levels);
+        self.dialog.borrow().selector().set_levels_map(levels);
     }
 
     // Helper method that initializes the qt widgets once the level map has been
@@ -331,6 +1337,6 @@ impl<'a> VpinDialog<'a> {
     // # Returns
     // * None
     fn set_levels_from_map(&self) {
-        self.dialog.borrow().set_levels_from_map();
+        self.dialog.borrow().selector().set_levels_from_map();
     }
 }
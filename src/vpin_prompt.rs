@@ -0,0 +1,94 @@
+//! Blocking convenience function which builds a VpinDialog, executes it, and returns
+//! the VpinSelection, hiding the `Rc` / `Slot` plumbing from simple consumers.
+use crate::vpin_dialog::{LevelMap, PinSpec, VpinDialog, VpinSelection};
+use qt_widgets::{cpp_core::MutPtr, QWidget};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Data used to initialize the dialog created by `prompt`.
+#[derive(Clone, Default)]
+pub struct PromptOptions {
+    /// The distributions the user may choose from. When empty, only the
+    /// distribution passed to `prompt` is offered.
+    pub distributions: Vec<String>,
+    /// The roles the user may choose from
+    pub roles: Vec<String>,
+    /// The sites the user may choose from
+    pub sites: Vec<String>,
+    /// Labels displayed in place of the site identifiers, keyed by identifier.
+    /// Sites without a label are displayed by their identifier.
+    pub site_labels: HashMap<String, String>,
+    /// The platforms the user may choose from. When empty, no platform is offered.
+    pub platforms: Vec<String>,
+    /// The sequences and shots the user may choose from
+    pub levels: LevelMap,
+    /// The pins already in place, against which conflicts are reported
+    pub existing_pins: Vec<PinSpec>,
+    /// Whether a comment must be given
+    pub comment_required: bool,
+    /// Whether a ticket must be given
+    pub ticket_required: bool,
+    /// Some regular expression the whole ticket must match. `prompt` panics on an
+    /// invalid pattern, so callers should check it with `validation::ticket_regex`
+    /// beforehand.
+    pub ticket_pattern: Option<String>,
+    /// Optional parent widget for the dialog
+    pub parent: Option<MutPtr<QWidget>>,
+}
+
+/// Create a VpinDialog for the supplied show and distribution, execute it, and return
+/// the selection if the user accepted the dialog. This blocks until the dialog is
+/// dismissed, and must be called from within `QApplication::init`. It panics if the
+/// ticket pattern is not a valid regular expression, rather than accepting any ticket.
+///
+/// # Arguments
+/// * `show` - The name of the show
+/// * `distribution` - The distribution we are setting a pin for
+/// * `options` - The distributions, roles, sites, platforms, levels, existing pins,
+///   comment and ticket rules, and parent used to initialize the dialog
+///
+/// # Returns
+/// * Some VpinSelection if the user pressed OK
+/// * None if the user cancelled the dialog
+pub unsafe fn prompt<I: Into<String>>(
+    show: I,
+    distribution: &str,
+    options: PromptOptions,
+) -> Option<VpinSelection> {
+    let parent = options.parent.unwrap_or_else(MutPtr::null);
+    let dialog = VpinDialog::create(show, distribution, parent);
+    dialog.set_default_stylesheet();
+    if !options.distributions.is_empty() {
+        dialog.set_distributions(options.distributions.iter().map(String::as_str).collect());
+    }
+    dialog.set_roles(options.roles.iter().map(String::as_str).collect());
+    dialog.set_site_labels(
+        options
+            .sites
+            .iter()
+            .map(|site| {
+                let label = options.site_labels.get(site).unwrap_or(site);
+                (site.as_str(), label.as_str())
+            })
+            .collect(),
+    );
+    dialog.set_platforms(options.platforms.iter().map(String::as_str).collect());
+    dialog.set_levels(options.levels);
+    dialog.set_existing_pins(options.existing_pins);
+    dialog.set_comment_required(options.comment_required);
+    dialog.set_ticket_required(options.ticket_required);
+    if let Err(err) = dialog.set_ticket_pattern(options.ticket_pattern.as_deref()) {
+        panic!("{}", err);
+    }
+
+    let selection = Rc::new(RefCell::new(None));
+    let accepted = selection.clone();
+    dialog.on_accept(move |sel: VpinSelection| {
+        accepted.replace(Some(sel));
+        Ok(())
+    });
+    dialog.dialog_mut().exec();
+    let result = selection.borrow_mut().take();
+    result
+}
@@ -0,0 +1,209 @@
+//! The VpinSelection captures the choices made by the user in the VpinDialog
+//! as plain data, so that consumers need not query the individual widgets.
+use crate::pin_spec::PinSpec;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+
+/// LevelMap maps a sequence to a list of shots
+pub type LevelMap = HashMap<String, Vec<String>>;
+
+/// VpinSelection describes the version pin the user has asked for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VpinSelection {
+    /// The name of the show
+    pub show: String,
+    /// The distributions being pinned. A single `distribution`, as written by
+    /// earlier versions, is also accepted when deserializing.
+    #[serde(alias = "distribution", deserialize_with = "one_or_many")]
+    pub distributions: Vec<String>,
+    /// Some specific roles, or None if the pin applies to any role
+    pub roles: Option<Vec<String>>,
+    /// Some level (`show.seq` or `show.seq.shot`), or None if the pin applies to the show
+    pub level: Option<String>,
+    /// Some specific site, or None if the pin applies to any site
+    pub site: Option<String>,
+    /// Some specific platforms (eg `linux-el9`), or None if the pin applies to any
+    /// platform
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platforms: Option<Vec<String>>,
+    /// Some comment explaining why the pin is needed, recorded with every pin
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Some ticket tracking the pin (eg `PROJ-123`), recorded with every pin
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket: Option<String>,
+    /// Some date (`YYYY-MM-DD`) from which the pins take effect, or None if they
+    /// take effect immediately
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_from: Option<String>,
+    /// Some date (`YYYY-MM-DD`) on which the pins expire, or None if they never expire
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_on: Option<String>,
+}
+
+impl VpinSelection {
+    /// Return the level the selection applies to, falling back to the show
+    /// when no sequence or shot has been chosen.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * The level name as a &str
+    pub fn level_or_show(&self) -> &str {
+        self.level.as_deref().unwrap_or(&self.show)
+    }
+
+    /// Expand the selection into the individual pins it describes: for each
+    /// distribution, one pin per selected role and platform, or a single pin for any
+    /// role and platform. Each pin carries the selection's comment, ticket and dates.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    /// * Vector of PinSpec
+    pub fn expand(&self) -> Vec<PinSpec> {
+        let roles = each_or_any(&self.roles);
+        let platforms = each_or_any(&self.platforms);
+        let scopes = roles
+            .iter()
+            .flat_map(|role| platforms.iter().map(move |platform| (role, platform)))
+            .collect::<Vec<_>>();
+        self.distributions
+            .iter()
+            .flat_map(|distribution| {
+                scopes.iter().map(move |(role, platform)| PinSpec {
+                    distribution: distribution.clone(),
+                    level: self.level_or_show().to_string(),
+                    role: (*role).clone(),
+                    site: self.site.clone(),
+                    platform: (*platform).clone(),
+                    comment: self.comment.clone(),
+                    ticket: self.ticket.clone(),
+                    effective_from: self.effective_from.clone(),
+                    expires_on: self.expires_on.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+// Wrap each of the specific values, or stand in a single None for any value
+fn each_or_any(values: &Option<Vec<String>>) -> Vec<Option<String>> {
+    match values {
+        Some(values) => values.iter().map(|value| Some(value.clone())).collect(),
+        None => vec![None],
+    }
+}
+
+// Deserialize either a single string or a list of strings into a vector
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection() -> VpinSelection {
+        VpinSelection {
+            show: "DEV01".to_string(),
+            distributions: vec![
+                "modelpublish-1.2.0".to_string(),
+                "texturepublish-2.0.0".to_string(),
+            ],
+            roles: Some(vec!["anim".to_string(), "model".to_string()]),
+            level: Some("DEV01.RD".to_string()),
+            site: Some("hyderabad".to_string()),
+            platforms: Some(vec!["linux-el7".to_string(), "linux-el9".to_string()]),
+            comment: Some("fixes the exporter".to_string()),
+            ticket: Some("PROJ-123".to_string()),
+            effective_from: None,
+            expires_on: Some("2026-12-31".to_string()),
+        }
+    }
+
+    // the distribution, role and platform of each pin
+    fn scopes(pins: &[PinSpec]) -> Vec<(&str, Option<&str>, Option<&str>)> {
+        pins.iter()
+            .map(|pin| {
+                (
+                    pin.distribution.as_str(),
+                    pin.role.as_deref(),
+                    pin.platform.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn expands_distributions_roles_and_platforms() {
+        let pins = selection().expand();
+        assert_eq!(
+            scopes(&pins),
+            vec![
+                ("modelpublish-1.2.0", Some("anim"), Some("linux-el7")),
+                ("modelpublish-1.2.0", Some("anim"), Some("linux-el9")),
+                ("modelpublish-1.2.0", Some("model"), Some("linux-el7")),
+                ("modelpublish-1.2.0", Some("model"), Some("linux-el9")),
+                ("texturepublish-2.0.0", Some("anim"), Some("linux-el7")),
+                ("texturepublish-2.0.0", Some("anim"), Some("linux-el9")),
+                ("texturepublish-2.0.0", Some("model"), Some("linux-el7")),
+                ("texturepublish-2.0.0", Some("model"), Some("linux-el9")),
+            ]
+        );
+        assert!(pins.iter().all(|pin| pin.level == "DEV01.RD"
+            && pin.site.as_deref() == Some("hyderabad")
+            && pin.ticket.as_deref() == Some("PROJ-123")
+            && pin.expires_on.as_deref() == Some("2026-12-31")));
+    }
+
+    #[test]
+    fn expands_to_any_role_and_platform() {
+        let selection = VpinSelection {
+            roles: None,
+            level: None,
+            platforms: None,
+            ..selection()
+        };
+        let pins = selection.expand();
+        assert_eq!(
+            scopes(&pins),
+            vec![
+                ("modelpublish-1.2.0", None, None),
+                ("texturepublish-2.0.0", None, None),
+            ]
+        );
+        assert_eq!(pins[0].level, "DEV01");
+    }
+
+    #[test]
+    fn expands_to_nothing_without_distributions() {
+        let selection = VpinSelection {
+            distributions: Vec::new(),
+            ..selection()
+        };
+        assert!(selection.expand().is_empty());
+    }
+
+    #[test]
+    fn accepts_a_single_distribution() {
+        let selection: VpinSelection = serde_json::from_str(
+            r#"{"show": "DEV01", "distribution": "modelpublish-1.2.0", "roles": null,
+                "level": null, "site": null}"#,
+        )
+        .unwrap();
+        assert_eq!(selection.distributions, vec!["modelpublish-1.2.0"]);
+        assert_eq!(selection.platforms, None);
+    }
+}
@@ -0,0 +1,1223 @@
+//! The VpinSelectorWidget provides the role / level / site / platform picker used
+//! by the VpinDialog as a standalone widget, so that it may be embedded in any layout.
+use crate::accessibility::{set_accessible, set_tab_chain, unlabelled_widgets};
+use crate::choice::Choice;
+use crate::i18n::{tr, trf};
+use crate::loader::Section;
+use crate::pin_spec::PinSpec;
+use crate::resolver::ResolveQuery;
+use crate::validation::{SelectionContext, SelectionError};
+use crate::vpin_selection::VpinSelection;
+use qt_core::{CheckState, FocusPolicy, ItemFlag, QString, QVariant, Slot, SlotOfInt};
+use qt_widgets::{
+    cpp_core::{CastInto, CppBox, MutPtr},
+    q_abstract_item_view::SelectionMode,
+    QCheckBox, QComboBox, QFrame, QGroupBox, QHBoxLayout, QLabel, QLayout, QLineEdit, QListWidget,
+    QListWidgetItem, QProgressBar, QPushButton, QVBoxLayout, QWidget,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub use crate::vpin_selection::LevelMap;
+pub use rustqt_utils::{create_hlayout, create_vlayout, qs};
+
+// Display text for the entries representing any sequence, shot or site. These
+// entries hold Choice::Any, which is what distinguishes them from specific values.
+const DEFAULT_SEQ: &str = "All Sequences";
+const DEFAULT_SHOT: &str = "All Shots";
+const DEFAULT_SITE: &str = "any";
+// The number of chips naming the selected roles. When more roles are selected, the
+// last chip reports how many others there are.
+const MAX_ROLE_CHIPS: usize = 6;
+
+/// VpinSelectorWidget hosts the controls used to choose the roles, level, site and
+/// platforms of a version pin. The platforms are only offered once some are set.
+/// It is parented to the supplied widget, and may be added to any layout via
+/// `widget()`.
+pub struct VpinSelectorWidget<'a> {
+    main: MutPtr<QWidget>,
+    show: RefCell<String>,
+    distribution: MutPtr<QLabel>,
+    distributions_list: MutPtr<QListWidget>,
+    roles_checkbox: MutPtr<QCheckBox>,
+    roles_group_box: MutPtr<QGroupBox>,
+    roles_loading: MutPtr<QProgressBar>,
+    roles_filter: MutPtr<QLineEdit>,
+    roles_all_button: MutPtr<QPushButton>,
+    roles_none_button: MutPtr<QPushButton>,
+    roles_invert_button: MutPtr<QPushButton>,
+    roles_list: MutPtr<QListWidget>,
+    roles_chips: Vec<MutPtr<QLabel>>,
+    roles_count: MutPtr<QLabel>,
+    seqs_cbox: MutPtr<QComboBox>,
+    seq_shot_checkbox: MutPtr<QCheckBox>,
+    levels_group_box: MutPtr<QGroupBox>,
+    levels_loading: MutPtr<QProgressBar>,
+    shots_cbox: MutPtr<QComboBox>,
+    resolution_label: MutPtr<QLabel>,
+    sites_cbox: MutPtr<QComboBox>,
+    sites_group_box: MutPtr<QGroupBox>,
+    sites_loading: MutPtr<QProgressBar>,
+    platforms_label: MutPtr<QLabel>,
+    platforms_list: MutPtr<QListWidget>,
+    platforms_group_box: MutPtr<QGroupBox>,
+    platforms_loading: MutPtr<QProgressBar>,
+    roles: RefCell<Vec<String>>,
+    sites: RefCell<Vec<String>>,
+    platforms: RefCell<Vec<String>>,
+    distributions: RefCell<Vec<String>>,
+    levels: Rc<RefCell<LevelMap>>,
+    seq_shot_cb_slot: SlotOfInt<'a>,
+    roles_cb_slot: SlotOfInt<'a>,
+    seq_changed: SlotOfInt<'a>,
+    roles_invert_slot: Slot<'a>,
+    roles_selection_changed: Slot<'a>,
+}
+
+impl<'a> VpinSelectorWidget<'a> {
+    /// Create a new VpinSelectorWidget
+    ///
+    /// # Arguments
+    /// * `show` - The name of the show
+    /// * `distribution` - The distribtuion we are setting a pin for
+    /// * `parent` - The parent widget which takes ownership of the selector
+    ///
+    /// # Returns
+    /// * VpinSelectorWidget instance
+    pub fn create<I: Into<String>>(
+        show: I,
+        distribution: &str,
+        parent: impl CastInto<MutPtr<QWidget>>,
+    ) -> VpinSelectorWidget {
+        unsafe {
+            // the selector is owned by its parent widget
+            let mut main = QWidget::new_1a(parent).into_ptr();
+            main.set_object_name(&qs("VpinSelectorWidget"));
+            let mut layout = create_vlayout();
+            let mut layout_ptr = layout.as_mut_ptr();
+            main.set_layout(layout.into_ptr());
+
+            Self::add_entry_label(layout_ptr);
+
+            let distribution_label = Self::add_distribution_label(distribution, layout_ptr);
+            let distributions_list = Self::add_distributions_listwidget(layout_ptr);
+
+            // hlayout will contain the two column  vertical layouts (left and right)
+            let mut hlayout = create_hlayout();
+            let hlayout_ptr = hlayout.as_mut_ptr();
+            layout_ptr.add_layout_1a(hlayout.into_ptr());
+
+            // Left side controls
+            let left_layout = Self::add_left_layout(hlayout_ptr);
+            let roles_checkbox = Self::add_roles_checkbox(left_layout);
+            let roles_loading = Self::add_loading_indicator(
+                left_layout,
+                "RolesLoadingIndicator",
+                tr("Loading roles").as_str(),
+            );
+            let mut group_box = Self::add_select_roles_groupbox(left_layout);
+            let (roles_filter, roles_filter_layout) = Self::add_roles_filter(group_box.layout());
+            let roles_all_button =
+                Self::add_roles_button(roles_filter_layout, "All", "RolesAllButton");
+            let roles_none_button =
+                Self::add_roles_button(roles_filter_layout, "None", "RolesNoneButton");
+            let roles_invert_button =
+                Self::add_roles_button(roles_filter_layout, "Invert", "RolesInvertButton");
+            let roles_list = Self::add_roles_listwidget(group_box.layout());
+            let (roles_chips, roles_count) = Self::add_roles_chips(group_box.layout());
+            let chips = roles_chips.clone();
+
+            // right side controls
+            let mut right_layout = Self::add_right_layout(hlayout_ptr);
+            //
+            let seq_shot_checkbox = Self::add_seq_shot_checkbox(right_layout);
+            let levels_loading = Self::add_loading_indicator(
+                right_layout,
+                "LevelsLoadingIndicator",
+                tr("Loading levels").as_str(),
+            );
+            let seq_shot_group_box = Self::add_select_level_groupbox(right_layout);
+            let seqs_cbox = Self::add_seq_cbox(seq_shot_group_box.layout());
+            let mut seqs_cbox_ref = seqs_cbox
+                .as_mut_ref()
+                .expect("unable to get mut ref to seq combobox from mutptr");
+            let shots_cbox = Self::add_shot_cbox(seq_shot_group_box.layout());
+            let resolution_label = Self::add_resolution_label(seq_shot_group_box.layout());
+            let mut shots_cbox_ref = shots_cbox
+                .as_mut_ref()
+                .expect("unable to get mut ref to shot combobox from mutptr");
+
+            let (sel_sites_group_box, mut sites_label) =
+                Self::add_select_site_groupbox(right_layout);
+            let sites_loading = Self::add_loading_indicator(
+                right_layout,
+                "SitesLoadingIndicator",
+                tr("Loading sites").as_str(),
+            );
+            let sites_cbox = Self::add_site_cbox(sel_sites_group_box.layout());
+            sites_label.set_buddy(sites_cbox);
+
+            let (platforms_group_box, mut platforms_label) =
+                Self::add_select_platform_groupbox(right_layout);
+            let platforms_loading = Self::add_loading_indicator(
+                right_layout,
+                "PlatformsLoadingIndicator",
+                tr("Loading platforms").as_str(),
+            );
+            let platforms_list = Self::add_platforms_listwidget(platforms_group_box.layout());
+            platforms_label.set_buddy(platforms_list);
+            right_layout.add_stretch_1a(1);
+
+            // create some references to components so that we can use them
+            // in Slots
+            let mut roles_list_ref = roles_list
+                .as_mut_ref()
+                .expect("unable to get reference to roles list");
+            let mut roles_filter_ref = roles_filter
+                .as_mut_ref()
+                .expect("unable to get ref to roles_filter");
+            // default to disabled
+            seqs_cbox_ref.set_enabled(false);
+            shots_cbox_ref.set_enabled(false);
+
+            roles_list_ref.set_enabled(false);
+            roles_filter_ref.set_enabled(false);
+            let levels = Rc::new(RefCell::new(LevelMap::new()));
+            let seq_levels = levels.clone();
+            // create the selector
+            let selector = VpinSelectorWidget {
+                main,
+                show: RefCell::new(show.into()),
+                distribution: distribution_label,
+                distributions_list,
+                roles_checkbox,
+                roles_group_box: group_box,
+                roles_loading,
+                roles_filter,
+                roles_all_button,
+                roles_none_button,
+                roles_invert_button,
+                roles_list,
+                roles_chips,
+                roles_count,
+                seq_shot_checkbox,
+                levels_group_box: seq_shot_group_box,
+                levels_loading,
+                seqs_cbox,
+                shots_cbox,
+                resolution_label,
+                sites_cbox,
+                sites_group_box: sel_sites_group_box,
+                sites_loading,
+                platforms_label,
+                platforms_list,
+                platforms_group_box,
+                platforms_loading,
+                roles: RefCell::new(Vec::new()),
+                sites: RefCell::new(Vec::new()),
+                platforms: RefCell::new(Vec::new()),
+                distributions: RefCell::new(Vec::new()),
+                levels,
+                seq_shot_cb_slot: SlotOfInt::new(move |active: std::os::raw::c_int| {
+                    if active > 0 {
+                        seqs_cbox_ref.set_enabled(true);
+                        shots_cbox_ref.set_enabled(true);
+                    } else {
+                        seqs_cbox_ref.set_enabled(false);
+                        shots_cbox_ref.set_enabled(false);
+                    }
+                }),
+                roles_cb_slot: SlotOfInt::new(move |active: std::os::raw::c_int| {
+                    if active > 0 {
+                        roles_list_ref.set_enabled(true);
+                        roles_filter_ref.set_enabled(true);
+                        group_box.set_enabled(true);
+                        roles_list_ref.set_focus_0a();
+                    } else {
+                        roles_list_ref.set_enabled(false);
+                        group_box.set_enabled(false);
+                        roles_filter_ref.set_enabled(false);
+                    }
+                }),
+                seq_changed: SlotOfInt::new(move |idx: std::os::raw::c_int| {
+                    let sequence = Self::choice_at(seqs_cbox, idx);
+                    Self::populate_shots(shots_cbox, &seq_levels.borrow(), &sequence);
+                }),
+                roles_invert_slot: Slot::new(move || {
+                    Self::invert_roles(roles_list);
+                }),
+                roles_selection_changed: Slot::new(move || {
+                    Self::update_roles_chips(roles_list, &chips, roles_count);
+                }),
+            };
+            // set up internal signals and slots
+            // Enable / Disable roles list and filter
+            selector
+                .seq_shot_checkbox
+                .state_changed()
+                .connect(&selector.seq_shot_cb_slot);
+            selector
+                .roles_checkbox
+                .state_changed()
+                .connect(&selector.roles_cb_slot);
+            // All, None and Invert operate on the roles list
+            roles_all_button
+                .clicked()
+                .connect(roles_list.slot_select_all());
+            roles_none_button
+                .clicked()
+                .connect(roles_list.slot_clear_selection());
+            roles_invert_button
+                .clicked()
+                .connect(&selector.roles_invert_slot);
+            // keep the role chips and the count of selected roles up to date
+            roles_list
+                .item_selection_changed()
+                .connect(&selector.roles_selection_changed);
+            // update the shots whenever the sequence changes
+            selector
+                .seqs_cbox
+                .current_index_changed()
+                .connect(&selector.seq_changed);
+            selector.set_distributions(vec![distribution]);
+            selector.setup_accessibility();
+            // set teh roles_lsit focus
+            let mut roles_list = selector.roles_list;
+            roles_list.set_focus_0a();
+            // clear the roles_filter focus
+            let mut roles_filter = selector.roles_filter;
+            roles_filter.clear_focus();
+
+            // return the selector
+            selector
+        }
+    }
+
+    /// Get a mutable pointer to the top level widget, so that it may be added to a layout
+    pub fn widget(&self) -> MutPtr<QWidget> {
+        self.main
+    }
+
+    /// retrieve the show_name
+    pub fn show_name(&self) -> String {
+        self.show.borrow().clone()
+    }
+    /// set the show name
+    pub fn set_show_name(&self, new_name: String) {
+        self.show.replace(new_name);
+    }
+    /// retrieve the current distribution as a string
+    pub unsafe fn distribution(&self) -> String {
+        self.distribution.text().to_std_string()
+    }
+    /// Retrieve an owned QString of the distribution text
+    pub unsafe fn distribution_qs(&self) -> CppBox<QString> {
+        self.distribution.text()
+    }
+    /// Set the distribution given a distribution &str, replacing any others
+    pub unsafe fn set_distribution(&self, distribution: &str) {
+        self.set_distributions(vec![distribution]);
+    }
+
+    /// Set the distributions on offer, each of which starts out checked. A single
+    /// distribution is displayed as a label, and several as a checkable list. The
+    /// first distribution is reported by `distribution()`.
+    pub fn set_distributions(&self, distributions: Vec<&str>) {
+        unsafe {
+            let mut distribution = self.distribution;
+            distribution.set_text(&qs(distributions.first().cloned().unwrap_or("")));
+            distribution.set_visible(distributions.len() < 2);
+            let mut distributions_list = self.distributions_list;
+            distributions_list.clear();
+            for name in &distributions {
+                let mut item = QListWidgetItem::from_q_string(&qs(name));
+                item.set_flags(item.flags() | ItemFlag::ItemIsUserCheckable);
+                item.set_check_state(CheckState::Checked);
+                distributions_list.add_item_q_list_widget_item(item.into_ptr());
+            }
+            distributions_list.set_visible(distributions.len() > 1);
+        }
+        self.distributions
+            .replace(distributions.into_iter().map(String::from).collect());
+    }
+
+    /// Retrieve the checked distributions, in the order in which they were set
+    pub unsafe fn selected_distributions(&self) -> Vec<String> {
+        let distributions_list = self.distributions_list;
+        (0..distributions_list.count())
+            .map(|idx| distributions_list.item(idx))
+            .filter(|item| item.check_state() == CheckState::Checked)
+            .map(|item| item.text().to_std_string())
+            .collect()
+    }
+
+    /// Check the supplied distributions, and uncheck the rest
+    pub unsafe fn set_selected_distributions(&self, distributions: &[&str]) {
+        let distributions_list = self.distributions_list;
+        for idx in 0..distributions_list.count() {
+            let mut item = distributions_list.item(idx);
+            let name = item.text().to_std_string();
+            if distributions.contains(&name.as_str()) {
+                item.set_check_state(CheckState::Checked);
+            } else {
+                item.set_check_state(CheckState::Unchecked);
+            }
+        }
+    }
+
+    /// Return a lsit of selected item names
+    pub unsafe fn selected_roles(&self) -> Option<Vec<String>> {
+        if !self.roles_checkbox.is_checked() {
+            return None;
+        };
+        let mut results = Vec::new();
+
+        if self.roles_list.is_null() {
+            panic!("roles_list pointer is null")
+        };
+        let mut items = self.roles_list.selected_items();
+        if !items.is_empty() {
+            for _c in 0..items.length() {
+                let item = items.take_first();
+                results.push(item.text().to_std_string());
+            }
+        }
+        Some(results)
+    }
+
+    /// Retrieve the current site, if specified. None indicates any site
+    pub unsafe fn selected_site(&self) -> Option<String> {
+        self.site_choice().into_option()
+    }
+
+    /// Retrieve the Choice behind the current site
+    pub unsafe fn site_choice(&self) -> Choice {
+        Self::current_choice(self.sites_cbox)
+    }
+
+    /// Retrieve the selected platforms, in the order in which they were set. An empty
+    /// list indicates that the pins are not restricted to any platform.
+    pub unsafe fn selected_platforms(&self) -> Vec<String> {
+        let platforms_list = self.platforms_list;
+        (0..platforms_list.count())
+            .map(|idx| platforms_list.item(idx))
+            .filter(|item| item.is_selected())
+            .map(|item| item.text().to_std_string())
+            .collect()
+    }
+
+    /// Gather the current state of the widgets into a VpinSelection. The comment,
+    /// ticket and dates are left for the hosting dialog to fill in.
+    pub unsafe fn selection(&self) -> VpinSelection {
+        VpinSelection {
+            show: self.show_name(),
+            distributions: self.selected_distributions(),
+            roles: self.selected_roles(),
+            level: self.selected_level(),
+            site: self.selected_site(),
+            platforms: Some(self.selected_platforms()).filter(|platforms| !platforms.is_empty()),
+            comment: None,
+            ticket: None,
+            effective_from: None,
+            expires_on: None,
+        }
+    }
+
+    /// Retrieve the distributions, roles, levels, sites and platforms on offer, against
+    /// which selections are validated
+    pub fn context(&self) -> SelectionContext {
+        SelectionContext {
+            distributions: self.distributions.borrow().clone(),
+            roles: self.roles.borrow().clone(),
+            levels: self.levels.borrow().clone(),
+            sites: self.sites.borrow().clone(),
+            platforms: self.platforms.borrow().clone(),
+            ..SelectionContext::default()
+        }
+    }
+
+    /// Gather the current state of the widgets into a VpinSelection, and validate it
+    /// against the roles, levels and sites on offer
+    pub unsafe fn validated_selection(&self) -> Result<VpinSelection, SelectionError> {
+        let selection = self.selection();
+        self.context().validate(&selection)?;
+        Ok(selection)
+    }
+
+    /// Return the controls to their initial state: roles, level, site and platforms
+    /// are unspecified and every role and distribution is selected.
+    pub unsafe fn reset(&self) {
+        let distributions = self.distributions.borrow().clone();
+        self.set_selected_distributions(
+            &distributions.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+        let mut roles_checkbox = self.roles_checkbox;
+        roles_checkbox.set_checked(false);
+        let mut roles_filter = self.roles_filter;
+        roles_filter.clear();
+        let mut roles_list = self.roles_list;
+        roles_list.select_all();
+        let mut seq_shot_checkbox = self.seq_shot_checkbox;
+        seq_shot_checkbox.set_checked(false);
+        let mut seqs_cbox = self.seqs_cbox;
+        seqs_cbox.set_current_index(0);
+        let mut sites_cbox = self.sites_cbox;
+        sites_cbox.set_current_index(0);
+        let mut platforms_list = self.platforms_list;
+        platforms_list.clear_selection();
+    }
+
+    /// Set the sites, displaying each by its name
+    pub fn set_sites(&self, sites: Vec<&str>) {
+        self.set_site_labels(sites.into_iter().map(|site| (site, site)).collect());
+    }
+
+    /// Set the sites along with the label displayed for each, eg
+    /// `("mtl", "Montreal (MTL)")`. Selections report the site's identifier.
+    pub fn set_site_labels(&self, sites: Vec<(&str, &str)>) {
+        unsafe {
+            let mut sites_cbox = self.sites_cbox;
+            sites_cbox.clear();
+            Self::add_choice_item(sites_cbox, tr(DEFAULT_SITE).as_str(), &Choice::Any);
+            for (site, label) in &sites {
+                Self::add_choice_item(sites_cbox, label, &Choice::specific(*site));
+            }
+        }
+        self.sites.replace(
+            sites
+                .into_iter()
+                .map(|(site, _)| site.to_string())
+                .collect(),
+        );
+    }
+
+    /// Set the platforms (eg `linux-el9`), none of which are selected. The platforms
+    /// are hidden while there are none to offer.
+    pub fn set_platforms(&self, platforms: Vec<&str>) {
+        unsafe {
+            let mut platforms_list = self.platforms_list;
+            platforms_list.clear();
+            for platform in &platforms {
+                platforms_list.add_item_q_string(&qs(platform));
+            }
+            let visible = !platforms.is_empty();
+            let mut platforms_label = self.platforms_label;
+            platforms_label.set_visible(visible);
+            let mut platforms_group_box = self.platforms_group_box;
+            platforms_group_box.set_visible(visible);
+        }
+        self.platforms
+            .replace(platforms.into_iter().map(String::from).collect());
+    }
+
+    /// set the list of rols. Every role starts out selected.
+    pub fn set_roles(&self, roles: Vec<&str>) {
+        unsafe {
+            let mut roles_list = self.roles_list;
+            roles_list.clear();
+            for role in &roles {
+                roles_list.add_item_q_string(&qs(role));
+            }
+            roles_list.select_all();
+            roles_list.set_focus_policy(FocusPolicy::StrongFocus);
+            Self::update_roles_chips(roles_list, &self.roles_chips, self.roles_count);
+        }
+        self.roles
+            .replace(roles.into_iter().map(String::from).collect());
+    }
+
+    /// Show or hide the loading indicator of a section, disabling the section's
+    /// controls while its data is loading. The existing pins have no section here.
+    pub unsafe fn set_section_loading(&self, section: Section, loading: bool) {
+        let mut indicator = match section {
+            Section::Roles => {
+                let mut roles_checkbox = self.roles_checkbox;
+                roles_checkbox.set_enabled(!loading);
+                let mut group_box = self.roles_group_box;
+                group_box.set_enabled(!loading && roles_checkbox.is_checked());
+                self.roles_loading
+            }
+            Section::Levels => {
+                let mut seq_shot_checkbox = self.seq_shot_checkbox;
+                seq_shot_checkbox.set_enabled(!loading);
+                let mut group_box = self.levels_group_box;
+                group_box.set_enabled(!loading);
+                self.levels_loading
+            }
+            Section::Sites => {
+                let mut group_box = self.sites_group_box;
+                group_box.set_enabled(!loading);
+                self.sites_loading
+            }
+            Section::Platforms => {
+                let mut group_box = self.platforms_group_box;
+                group_box.set_enabled(!loading);
+                self.platforms_loading
+            }
+            Section::ExistingPins => return,
+        };
+        indicator.set_visible(loading);
+    }
+
+    /// provide a vector of strings representing the sequences stored in the map
+    pub fn seqs(&self) -> Vec<String> {
+        self.levels
+            .borrow()
+            .keys()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+    }
+
+    pub unsafe fn selected_seq(&self) -> Option<String> {
+        self.seq_choice().into_option()
+    }
+
+    pub unsafe fn selected_shot(&self) -> Option<String> {
+        self.shot_choice().into_option()
+    }
+
+    /// Retrieve the Choice behind the current sequence. This is Choice::Any
+    /// while the sequence combobox is disabled
+    pub unsafe fn seq_choice(&self) -> Choice {
+        if !self.seqs_cbox.is_enabled() {
+            return Choice::Any;
+        }
+        Self::current_choice(self.seqs_cbox)
+    }
+
+    /// Retrieve the Choice behind the current shot. This is Choice::Any
+    /// while the shot combobox is disabled
+    pub unsafe fn shot_choice(&self) -> Choice {
+        if !self.shots_cbox.is_enabled() {
+            return Choice::Any;
+        }
+        Self::current_choice(self.shots_cbox)
+    }
+
+    /// Return the selected Sequence/shot if applicable
+    pub unsafe fn selected_level(&self) -> Option<String> {
+        if !self.seq_shot_checkbox.is_checked() {
+            return None;
+        }
+        let seq = self.selected_seq()?;
+        Some(match self.selected_shot() {
+            Some(shot) => format!("{}.{}.{}", self.show.borrow(), seq, shot),
+            None => format!("{}.{}", self.show.borrow(), seq),
+        })
+    }
+
+    /// Build a query for the pin currently in effect for the package, at the level
+    /// and site selected. The role and platform are only taken into account when
+    /// exactly one of each is selected.
+    pub unsafe fn resolve_query(&self, package: &str) -> ResolveQuery {
+        let (seq, shot) = if self.seq_shot_checkbox.is_checked() {
+            (self.selected_seq(), self.selected_shot())
+        } else {
+            (None, None)
+        };
+        let role = match self.selected_roles() {
+            Some(ref roles) if roles.len() == 1 => roles.first().cloned(),
+            _ => None,
+        };
+        let platforms = self.selected_platforms();
+        let platform = if platforms.len() == 1 {
+            platforms.first().cloned()
+        } else {
+            None
+        };
+        ResolveQuery {
+            package: package.to_string(),
+            show: self.show_name(),
+            seq,
+            shot,
+            role,
+            site: self.selected_site(),
+            platform,
+        }
+    }
+
+    /// Display the pin currently in effect for each package alongside the sequence
+    /// and shot. An empty slice hides the display.
+    pub unsafe fn set_resolutions(&self, resolutions: &[(String, Option<PinSpec>)]) {
+        let mut resolution_label = self.resolution_label;
+        let text = resolutions
+            .iter()
+            .map(|(package, pin)| match pin {
+                Some(pin) => trf(
+                    "{}: currently resolves to {} (pinned at {})",
+                    &[package, &pin.distribution, &pin.level],
+                ),
+                None => trf("{}: not currently pinned", &[package]),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        resolution_label.set_text(&qs(text.as_str()));
+        resolution_label.set_visible(!resolutions.is_empty());
+    }
+
+    /// Initialize the sequences and shots given the provided LevelMap
+    pub fn set_levels(&self, levels: LevelMap) {
+        self.set_levels_map(levels);
+        self.set_levels_from_map();
+    }
+
+    /// Given a new LevelMap, repalace the existing one
+    pub fn set_levels_map(&self, levels: LevelMap) {
+        self.levels.replace(levels);
+    }
+
+    /// Initialize the qt widgets from the current LevelMap
+    pub fn set_levels_from_map(&self) {
+        unsafe {
+            let mut seqs_cbox = self.seqs_cbox;
+            let mut shots_cbox = self.shots_cbox;
+            seqs_cbox.clear();
+            Self::add_choice_item(seqs_cbox, tr(DEFAULT_SEQ).as_str(), &Choice::Any);
+            for seq in self.levels.borrow().keys() {
+                Self::add_choice_item(seqs_cbox, seq, &Choice::specific(seq.as_str()));
+            }
+            shots_cbox.clear();
+            Self::add_choice_item(shots_cbox, tr(DEFAULT_SHOT).as_str(), &Choice::Any);
+        }
+    }
+
+    pub unsafe fn clear_shots(&self) {
+        let mut shots_cbox = self.shots_cbox;
+        shots_cbox.clear();
+    }
+    /// Given a sequence from a selection, populate the shot combobox
+    pub unsafe fn set_shots_for_seq(&self, sequence: &str) {
+        Self::populate_shots(
+            self.shots_cbox,
+            &self.levels.borrow(),
+            &Choice::specific(sequence),
+        );
+    }
+
+    pub unsafe fn set_roles_focus(&self) {
+        let mut roles_filter = self.roles_filter;
+        roles_filter.set_focus_0a();
+    }
+
+    /// Toggle whether specific roles are requested
+    pub unsafe fn toggle_roles(&self) {
+        let mut roles_checkbox = self.roles_checkbox;
+        roles_checkbox.toggle();
+    }
+
+    /// Toggle whether a specific sequence / shot is requested
+    pub unsafe fn toggle_levels(&self) {
+        let mut seq_shot_checkbox = self.seq_shot_checkbox;
+        seq_shot_checkbox.toggle();
+    }
+
+    /// Request specific roles and move focus to the roles filter
+    pub unsafe fn focus_roles_filter(&self) {
+        let mut roles_checkbox = self.roles_checkbox;
+        roles_checkbox.set_checked(true);
+        self.set_roles_focus();
+    }
+
+    /// Select every role
+    pub unsafe fn select_all_roles(&self) {
+        let mut roles_list = self.roles_list;
+        roles_list.select_all();
+    }
+
+    /// Deselect every role
+    pub unsafe fn select_no_roles(&self) {
+        let mut roles_list = self.roles_list;
+        roles_list.clear_selection();
+    }
+
+    /// Invert the selection of roles
+    pub unsafe fn invert_selected_roles(&self) {
+        Self::invert_roles(self.roles_list);
+    }
+
+    /// Select the supplied roles, deselecting the rest, and request specific roles.
+    /// Roles which are not on offer are ignored.
+    pub unsafe fn set_selected_roles(&self, roles: &[&str]) {
+        let roles_list = self.roles_list;
+        for idx in 0..roles_list.count() {
+            let mut item = roles_list.item(idx);
+            let role = item.text().to_std_string();
+            item.set_selected(roles.contains(&role.as_str()));
+        }
+        let mut roles_checkbox = self.roles_checkbox;
+        roles_checkbox.set_checked(true);
+    }
+
+    pub fn seqs_cb(&self) -> MutPtr<QComboBox> {
+        self.seqs_cbox
+    }
+
+    // Populate the shots combobox with the shots belonging to the supplied
+    // sequence. This is shared between set_shots_for_seq and the seq_changed slot
+    unsafe fn populate_shots(
+        mut shots_cbox: MutPtr<QComboBox>,
+        levels: &LevelMap,
+        sequence: &Choice,
+    ) {
+        shots_cbox.clear();
+        Self::add_choice_item(shots_cbox, tr(DEFAULT_SHOT).as_str(), &Choice::Any);
+        if let Some(shots) = sequence.id().and_then(|seq| levels.get(seq)) {
+            for shot in shots {
+                Self::add_choice_item(shots_cbox, shot, &Choice::specific(shot.as_str()));
+            }
+        }
+    }
+
+    // Add an entry to the combobox, displaying the label and storing the Choice as
+    // item data. Choice::Any is stored as an invalid QVariant, so that it cannot be
+    // mistaken for a specific value, whatever its label
+    unsafe fn add_choice_item(mut cbox: MutPtr<QComboBox>, label: &str, choice: &Choice) {
+        let data = match choice.id() {
+            Some(id) => QVariant::from_q_string(&qs(id)),
+            None => QVariant::new(),
+        };
+        cbox.add_item_q_string_q_variant(&qs(label), &data);
+    }
+
+    // Retrieve the Choice stored in the entry of the combobox at the supplied index
+    unsafe fn choice_at(cbox: MutPtr<QComboBox>, idx: std::os::raw::c_int) -> Choice {
+        let data = cbox.item_data_1a(idx);
+        if data.is_valid() {
+            Choice::Specific(data.to_string().to_std_string())
+        } else {
+            Choice::Any
+        }
+    }
+
+    // Retrieve the Choice stored in the current entry of the combobox
+    unsafe fn current_choice(cbox: MutPtr<QComboBox>) -> Choice {
+        Self::choice_at(cbox, cbox.current_index())
+    }
+
+    /// Connect the supplied slot to every signal which changes the selection, so
+    /// that it is invoked whenever the user alters the distributions, roles, level,
+    /// site or platforms
+    pub unsafe fn connect_selection_changed(&self, slot: &Slot<'a>) {
+        self.distributions_list.item_changed().connect(slot);
+        self.roles_checkbox.state_changed().connect(slot);
+        self.roles_list.item_selection_changed().connect(slot);
+        self.seq_shot_checkbox.state_changed().connect(slot);
+        self.seqs_cbox.current_index_changed().connect(slot);
+        self.shots_cbox.current_index_changed().connect(slot);
+        self.sites_cbox.current_index_changed().connect(slot);
+        self.platforms_list.item_selection_changed().connect(slot);
+    }
+
+    /// Retrieve the interactive widgets, in tab order. Every focusable widget in the
+    /// selector should be listed.
+    pub unsafe fn interactive_widgets(&self) -> Vec<MutPtr<QWidget>> {
+        vec![
+            self.distributions_list.cast_into(),
+            self.roles_checkbox.cast_into(),
+            self.roles_filter.cast_into(),
+            self.roles_all_button.cast_into(),
+            self.roles_none_button.cast_into(),
+            self.roles_invert_button.cast_into(),
+            self.roles_list.cast_into(),
+            self.seq_shot_checkbox.cast_into(),
+            self.seqs_cbox.cast_into(),
+            self.shots_cbox.cast_into(),
+            self.sites_cbox.cast_into(),
+            self.platforms_list.cast_into(),
+        ]
+    }
+
+    /// Retrieve the object names of any focusable widgets in the selector which
+    /// lack an accessible name. This should always be empty.
+    pub unsafe fn unlabelled_widgets(&self) -> Vec<String> {
+        unlabelled_widgets(self.main)
+    }
+
+    // Give each interactive widget an accessible name and description, and
+    // chain the tab order from the distributions down the left column and then the
+    // right column
+    unsafe fn setup_accessibility(&self) {
+        set_accessible(
+            self.distributions_list,
+            tr("Distributions").as_str(),
+            tr("The distributions to pin").as_str(),
+        );
+        set_accessible(
+            self.roles_checkbox,
+            tr("Specify roles").as_str(),
+            tr("Restrict the pin to the roles selected in the roles list").as_str(),
+        );
+        set_accessible(
+            self.roles_filter,
+            tr("Roles filter").as_str(),
+            tr("Filter the roles list by name").as_str(),
+        );
+        set_accessible(
+            self.roles_all_button,
+            tr("Select all roles").as_str(),
+            tr("Select every role in the roles list").as_str(),
+        );
+        set_accessible(
+            self.roles_none_button,
+            tr("Select no roles").as_str(),
+            tr("Deselect every role in the roles list").as_str(),
+        );
+        set_accessible(
+            self.roles_invert_button,
+            tr("Invert role selection").as_str(),
+            tr("Select the unselected roles and deselect the selected roles").as_str(),
+        );
+        set_accessible(
+            self.roles_list,
+            tr("Roles").as_str(),
+            tr("The roles the pin applies to").as_str(),
+        );
+        set_accessible(
+            self.seq_shot_checkbox,
+            tr("Specify sequence or shot").as_str(),
+            tr("Restrict the pin to the selected sequence or shot").as_str(),
+        );
+        set_accessible(
+            self.seqs_cbox,
+            tr("Sequence").as_str(),
+            tr("The sequence the pin applies to").as_str(),
+        );
+        set_accessible(
+            self.shots_cbox,
+            tr("Shot").as_str(),
+            tr("The shot the pin applies to").as_str(),
+        );
+        set_accessible(
+            self.sites_cbox,
+            tr("Site").as_str(),
+            tr("The site the pin applies to").as_str(),
+        );
+        set_accessible(
+            self.platforms_list,
+            tr("Platforms").as_str(),
+            tr("The platforms the pin applies to. Select none for any platform").as_str(),
+        );
+        set_tab_chain(&self.interactive_widgets());
+    }
+
+    // Invert the selection state of every role in the list
+    unsafe fn invert_roles(roles_list: MutPtr<QListWidget>) {
+        for idx in 0..roles_list.count() {
+            let mut item = roles_list.item(idx);
+            item.set_selected(!item.is_selected());
+        }
+    }
+
+    // Update the chips naming the selected roles, in list order, along with the label
+    // reporting the number of roles selected. Should there be more selected roles
+    // than chips, the last chip reports how many are not shown.
+    unsafe fn update_roles_chips(
+        roles_list: MutPtr<QListWidget>,
+        chips: &[MutPtr<QLabel>],
+        mut roles_count: MutPtr<QLabel>,
+    ) {
+        let total = roles_list.count();
+        let selected = (0..total)
+            .map(|idx| roles_list.item(idx))
+            .filter(|item| item.is_selected())
+            .map(|item| item.text().to_std_string())
+            .collect::<Vec<_>>();
+        let overflow = selected.len() > chips.len();
+        let named = if overflow {
+            chips.len() - 1
+        } else {
+            selected.len()
+        };
+        for (idx, chip) in chips.iter().enumerate() {
+            let mut chip = *chip;
+            if idx < named {
+                chip.set_text(&qs(selected[idx].as_str()));
+                chip.set_visible(true);
+            } else if overflow && idx == named {
+                let others = selected.len() - named;
+                chip.set_text(&qs(trf("+{} more", &[&others]).as_str()));
+                chip.set_visible(true);
+            } else {
+                chip.clear();
+                chip.set_visible(false);
+            }
+        }
+        roles_count.set_text(&qs(
+            trf("{} of {} selected", &[&selected.len(), &total]).as_str()
+        ));
+    }
+
+    // add a busy indicator, hidden until the data it stands in for is loading
+    unsafe fn add_loading_indicator(
+        mut parent: MutPtr<QVBoxLayout>,
+        object_name: &str,
+        name: &str,
+    ) -> MutPtr<QProgressBar> {
+        let mut indicator = QProgressBar::new_0a();
+        indicator.set_object_name(&qs(object_name));
+        // a range of 0..0 makes the progress bar a busy indicator
+        indicator.set_range(0, 0);
+        indicator.set_text_visible(false);
+        indicator.set_visible(false);
+        let indicator_ptr = indicator.as_mut_ptr();
+        set_accessible(
+            indicator_ptr,
+            name,
+            tr("Shown while the data is loading").as_str(),
+        );
+        parent.add_widget(indicator.into_ptr());
+        indicator_ptr
+    }
+
+    unsafe fn add_site_cbox(mut parent: MutPtr<QLayout>) -> MutPtr<QComboBox> {
+        let mut sites_cbox = QComboBox::new_0a();
+        sites_cbox.set_object_name(&qs("SelectLocationComboBox"));
+        let sites_cbox_ptr = sites_cbox.as_mut_ptr();
+        parent.add_widget(sites_cbox.into_ptr());
+        sites_cbox_ptr
+    }
+
+    // add the site group box, returning it along with its label, so that the
+    // label may be made the buddy of the site combobox
+    unsafe fn add_select_site_groupbox(
+        mut parent: MutPtr<QVBoxLayout>,
+    ) -> (MutPtr<QGroupBox>, MutPtr<QLabel>) {
+        let mut label = QLabel::from_q_string(&qs(tr("Select Site").as_str()));
+        label.set_object_name(&qs("SelectSiteLabel"));
+        let label_ptr = label.as_mut_ptr();
+        parent.add_widget(label.into_ptr());
+        let mut group_box = QGroupBox::new();
+        let group_box_ptr = group_box.as_mut_ptr();
+        group_box.set_object_name(&qs("SelectSiteGroupBox"));
+        let layout = create_vlayout();
+        group_box.set_layout(layout.into_ptr());
+        parent.add_widget(group_box.into_ptr());
+        (group_box_ptr, label_ptr)
+    }
+
+    // add the platform group box, returning it along with its label, so that the
+    // label may be made the buddy of the platforms list. Both stay hidden until
+    // there are platforms to offer
+    unsafe fn add_select_platform_groupbox(
+        mut parent: MutPtr<QVBoxLayout>,
+    ) -> (MutPtr<QGroupBox>, MutPtr<QLabel>) {
+        let mut label = QLabel::from_q_string(&qs(tr("Select Platforms").as_str()));
+        label.set_object_name(&qs("SelectPlatformsLabel"));
+        label.set_visible(false);
+        let label_ptr = label.as_mut_ptr();
+        parent.add_widget(label.into_ptr());
+        let mut group_box = QGroupBox::new();
+        let group_box_ptr = group_box.as_mut_ptr();
+        group_box.set_object_name(&qs("SelectPlatformsGroupBox"));
+        group_box.set_visible(false);
+        let layout = create_vlayout();
+        group_box.set_layout(layout.into_ptr());
+        parent.add_widget(group_box.into_ptr());
+        (group_box_ptr, label_ptr)
+    }
+
+    // add the platforms list. Clicking a platform toggles it, and selecting none
+    // stands for any platform
+    unsafe fn add_platforms_listwidget(mut parent: MutPtr<QLayout>) -> MutPtr<QListWidget> {
+        let mut list_widget = QListWidget::new_0a();
+        list_widget.set_object_name(&qs("PlatformsListWidget"));
+        list_widget.set_selection_mode(SelectionMode::MultiSelection);
+        let list_widget_ptr = list_widget.as_mut_ptr();
+        parent.add_widget(list_widget.into_ptr());
+        list_widget_ptr
+    }
+
+    unsafe fn add_seq_shot_checkbox(mut parent: MutPtr<QVBoxLayout>) -> MutPtr<QCheckBox> {
+        let mut cb = QCheckBox::from_q_string(&qs(tr("Specify Seq(s) / Shot(s)").as_str()));
+        cb.set_object_name(&qs("SeqShotCheckBox"));
+        let cb_ptr = cb.as_mut_ptr();
+        parent.add_widget(cb.into_ptr());
+        cb_ptr
+    }
+
+    unsafe fn add_seq_cbox(mut parent: MutPtr<QLayout>) -> MutPtr<QComboBox> {
+        let mut seqs_cbox = QComboBox::new_0a();
+        seqs_cbox.set_object_name(&qs("AddSeqsComboBox"));
+        let seqs_cbox_ptr = seqs_cbox.as_mut_ptr();
+        Self::add_choice_item(seqs_cbox_ptr, tr(DEFAULT_SEQ).as_str(), &Choice::Any);
+        parent.add_widget(seqs_cbox.into_ptr());
+        seqs_cbox_ptr
+    }
+
+    unsafe fn add_shot_cbox(mut parent: MutPtr<QLayout>) -> MutPtr<QComboBox> {
+        let mut shots_cbox = QComboBox::new_0a();
+        shots_cbox.set_object_name(&qs("AddShotsComboBox"));
+        let shots_cbox_ptr = shots_cbox.as_mut_ptr();
+        Self::add_choice_item(shots_cbox_ptr, tr(DEFAULT_SHOT).as_str(), &Choice::Any);
+        parent.add_widget(shots_cbox.into_ptr());
+        shots_cbox_ptr
+    }
+
+    // add the label reporting the pin currently in effect. It is hidden until
+    // there are existing pins to resolve against
+    unsafe fn add_resolution_label(mut parent: MutPtr<QLayout>) -> MutPtr<QLabel> {
+        let mut label = QLabel::new();
+        label.set_object_name(&qs("ResolutionLabel"));
+        label.set_word_wrap(true);
+        label.set_visible(false);
+        let label_ptr = label.as_mut_ptr();
+        parent.add_widget(label.into_ptr());
+        label_ptr
+    }
+
+    unsafe fn add_select_level_groupbox(mut parent: MutPtr<QVBoxLayout>) -> MutPtr<QGroupBox> {
+        let mut group_box = QGroupBox::new();
+        let group_box_ptr = group_box.as_mut_ptr();
+        group_box.set_object_name(&qs("SelectLevelsGroupBox"));
+        let layout = create_vlayout();
+        group_box.set_layout(layout.into_ptr());
+        parent.add_widget(group_box.into_ptr());
+        group_box_ptr
+    }
+
+    unsafe fn add_roles_checkbox(mut parent: MutPtr<QVBoxLayout>) -> MutPtr<QCheckBox> {
+        let mut cb = QCheckBox::from_q_string(&qs(tr("Specify Roles").as_str()));
+        cb.set_object_name(&qs("RolesCheckBox"));
+        let cb_ptr = cb.as_mut_ptr();
+        parent.add_widget(cb.into_ptr());
+        cb_ptr
+    }
+    // add the checkable list of distributions beneath the distribution label. It is
+    // hidden until more than one distribution is on offer
+    unsafe fn add_distributions_listwidget(mut parent: MutPtr<QVBoxLayout>) -> MutPtr<QListWidget> {
+        let mut list_widget = QListWidget::new_0a();
+        list_widget.set_object_name(&qs("DistributionsListWidget"));
+        list_widget.set_selection_mode(SelectionMode::NoSelection);
+        list_widget.set_visible(false);
+        let list_widget_ptr = list_widget.as_mut_ptr();
+        parent.add_widget(list_widget.into_ptr());
+        list_widget_ptr
+    }
+
+    unsafe fn add_roles_listwidget(mut parent: MutPtr<QLayout>) -> MutPtr<QListWidget> {
+        let mut list_widget = QListWidget::new_0a();
+        list_widget.set_object_name(&qs("RolesListWidget"));
+        list_widget.set_selection_mode(SelectionMode::ExtendedSelection);
+        let list_widget_ptr = list_widget.as_mut_ptr();
+        parent.add_widget(list_widget.into_ptr());
+        list_widget_ptr
+    }
+
+    // add the filter line edit, returning it along with the layout it shares with
+    // the All / None / Invert buttons
+    unsafe fn add_roles_filter(
+        parent: MutPtr<QLayout>,
+    ) -> (MutPtr<QLineEdit>, MutPtr<QHBoxLayout>) {
+        let mut hlayout = create_hlayout();
+        let mut hlayout_ptr = hlayout.as_mut_ptr();
+        let mut parent: MutPtr<QVBoxLayout> = parent.dynamic_cast_mut();
+        if parent.is_null() {
+            panic!("unable to cast layout");
+        }
+        parent.add_layout_1a(hlayout.into_ptr());
+        let mut label = QLabel::from_q_string(&qs(tr("Filter:").as_str()));
+        label.set_object_name(&qs("RolesFilterLabel"));
+        let mut line_edit = QLineEdit::new();
+        line_edit.set_object_name(&qs("RolesFilterLineEdit"));
+        let line_edit_ptr = line_edit.as_mut_ptr();
+        label.set_buddy(line_edit_ptr);
+        hlayout_ptr.add_widget(label.into_ptr());
+        hlayout_ptr.add_widget(line_edit.into_ptr());
+        (line_edit_ptr, hlayout_ptr)
+    }
+
+    unsafe fn add_roles_button(
+        mut parent: MutPtr<QHBoxLayout>,
+        text: &str,
+        name: &str,
+    ) -> MutPtr<QPushButton> {
+        let mut button = QPushButton::from_q_string(&qs(tr(text).as_str()));
+        button.set_object_name(&qs(name));
+        let button_ptr = button.as_mut_ptr();
+        parent.add_widget(button.into_ptr());
+        button_ptr
+    }
+
+    // add a row of chips naming the selected roles, hidden until roles are selected,
+    // followed by the count of selected roles
+    unsafe fn add_roles_chips(parent: MutPtr<QLayout>) -> (Vec<MutPtr<QLabel>>, MutPtr<QLabel>) {
+        let mut hlayout = create_hlayout();
+        let mut hlayout_ptr = hlayout.as_mut_ptr();
+        let mut parent: MutPtr<QVBoxLayout> = parent.dynamic_cast_mut();
+        if parent.is_null() {
+            panic!("unable to cast layout");
+        }
+        parent.add_layout_1a(hlayout.into_ptr());
+        let chips = (0..MAX_ROLE_CHIPS)
+            .map(|_| {
+                let mut chip = QLabel::new();
+                chip.set_object_name(&qs("RoleChip"));
+                chip.set_visible(false);
+                let chip_ptr = chip.as_mut_ptr();
+                hlayout_ptr.add_widget(chip.into_ptr());
+                chip_ptr
+            })
+            .collect::<Vec<_>>();
+        hlayout_ptr.add_stretch_1a(1);
+        let mut label = QLabel::new();
+        label.set_object_name(&qs("RolesCountLabel"));
+        let label_ptr = label.as_mut_ptr();
+        hlayout_ptr.add_widget(label.into_ptr());
+        (chips, label_ptr)
+    }
+
+    unsafe fn add_select_roles_groupbox(mut parent: MutPtr<QVBoxLayout>) -> MutPtr<QGroupBox> {
+        let mut group_box = QGroupBox::new();
+        let mut group_box_ptr = group_box.as_mut_ptr();
+        group_box.set_object_name(&qs("SelectRolesGroupBox"));
+        parent.add_widget(group_box.into_ptr());
+        let layout = create_vlayout();
+        group_box_ptr.set_layout(layout.into_ptr());
+        // we default to disabled
+        group_box_ptr.set_enabled(false);
+        group_box_ptr
+    }
+    unsafe fn add_right_layout(mut parent: MutPtr<QHBoxLayout>) -> MutPtr<QVBoxLayout> {
+        let mut layout_right = create_vlayout();
+        let layout_right_ptr = layout_right.as_mut_ptr();
+        parent.add_layout_1a(layout_right.into_ptr());
+        layout_right_ptr
+    }
+    unsafe fn add_left_layout(mut parent: MutPtr<QHBoxLayout>) -> MutPtr<QVBoxLayout> {
+        let mut layout_left = create_vlayout();
+        let layout_left_ptr = layout_left.as_mut_ptr();
+        parent.add_layout_1a(layout_left.into_ptr());
+        layout_left_ptr
+    }
+    // add the add_entry label to the left hand side
+    unsafe fn add_entry_label(mut parent: MutPtr<QVBoxLayout>) {
+        // add label
+        let mut entry_frame = QFrame::new_0a();
+        let mut entry_frame_ptr = entry_frame.as_mut_ptr();
+        parent.add_widget(entry_frame.into_ptr());
+        let mut add_entries = QLabel::from_q_string(&qs(tr("Add Entry").as_str()));
+        add_entries.set_object_name(&qs("AddEntriesLabel"));
+        let mut add_entry_layout = create_vlayout();
+        add_entry_layout.add_widget(add_entries.into_ptr());
+        entry_frame_ptr.set_layout(add_entry_layout.into_ptr());
+    }
+
+    // add the distribution label in the middle of the widget
+    unsafe fn add_distribution_label(
+        distribution: &str,
+        mut parent: MutPtr<QVBoxLayout>,
+    ) -> MutPtr<QLabel> {
+        // layout is the top level layout for the widget
+        let mut dist_frame = QFrame::new_0a();
+        let mut distribution = QLabel::from_q_string(&qs(distribution));
+        let distribution_ptr = distribution.as_mut_ptr();
+        distribution.set_object_name(&qs("DistributionLabel"));
+        let mut add_entry_layout = create_hlayout();
+        add_entry_layout.add_stretch_1a(1);
+        add_entry_layout.add_widget(distribution.into_ptr());
+        add_entry_layout.add_stretch_1a(1);
+        dist_frame.set_layout(add_entry_layout.into_ptr());
+        parent.add_widget(dist_frame.into_ptr());
+        distribution_ptr
+    }
+}
@@ -0,0 +1,56 @@
+//! Checks that every focusable widget in the VpinDialog, and in the standalone
+//! VpinSelectorWidget, has an accessible name for screen readers. The widget tree
+//! is walked from the top, so that widgets added later are checked too.
+//!
+//! Qt requires the QApplication to live on the main thread, so this test provides
+//! its own main rather than using the default harness. It runs on the offscreen
+//! platform unless another is requested via `QT_QPA_PLATFORM`.
+use pbgui_vpin::accessibility::focusable_widgets;
+use pbgui_vpin::vpin_dialog::{LevelMap, VpinDialog};
+use pbgui_vpin::vpin_selector_widget::VpinSelectorWidget;
+use qt_widgets::{
+    cpp_core::{CastInto, MutPtr},
+    QApplication, QWidget,
+};
+
+fn main() {
+    if std::env::var_os("QT_QPA_PLATFORM").is_none() {
+        std::env::set_var("QT_QPA_PLATFORM", "offscreen");
+    }
+    QApplication::init(|_app| unsafe {
+        let mut main = QWidget::new_0a();
+        let dialog = VpinDialog::create("DEV01", "modelpublish-1.2.0", main.as_mut_ptr());
+        dialog.set_distributions(vec!["modelpublish-1.2.0", "modelpublish-plugin-1.2.0"]);
+        dialog.set_roles(vec!["anim", "model", "fx"]);
+        dialog.set_sites(vec!["montreal", "vancouver"]);
+        dialog.set_platforms(vec!["linux-el7", "linux-el9"]);
+        let mut levels = LevelMap::new();
+        levels.insert("RD".to_string(), vec!["0001".to_string()]);
+        dialog.set_levels(levels);
+        let selector = VpinSelectorWidget::create("DEV01", "modelpublish-1.2.0", main.as_mut_ptr());
+
+        let mut failures = 0;
+        let roots: [(&str, MutPtr<QWidget>); 2] = [
+            ("VpinDialog", dialog.dialog_mut().cast_into()),
+            ("VpinSelectorWidget", selector.widget()),
+        ];
+        for (name, root) in &roots {
+            let widgets = focusable_widgets(*root);
+            let unlabelled = widgets
+                .iter()
+                .filter(|widget| widget.accessible_name().is_empty())
+                .map(|widget| widget.object_name().to_std_string())
+                .collect::<Vec<_>>();
+            if widgets.is_empty() {
+                println!("test {} is labelled ... FAILED: no focusable widgets", name);
+                failures += 1;
+            } else if unlabelled.is_empty() {
+                println!("test {} is labelled ... ok", name);
+            } else {
+                println!("test {} is labelled ... FAILED: {:?}", name, unlabelled);
+                failures += 1;
+            }
+        }
+        failures
+    });
+}